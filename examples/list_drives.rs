@@ -16,7 +16,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         } else {
             "no audio CD"
         };
-        println!("Drive: {}, status: [{status}]", drive.path);
+        println!("Drive: {drive}, status: [{status}]");
     }
 
     Ok(())
@@ -0,0 +1,509 @@
+//! [AccurateRip](http://www.accuraterip.com/) disc IDs, track checksums and
+//! database response parsing.
+//!
+//! A rip is verified by computing the v1 and v2 checksums of every audio track
+//! and comparing them with the entries in the `dBAR-*.bin` response that
+//! [`AccurateRipDiscId::url`] points to.
+
+use std::io;
+
+use crate::{CdReaderError, Toc, utils};
+
+const SAMPLES_PER_SECTOR: u32 = 588;
+const BYTES_PER_SAMPLE: usize = 4;
+/// The first and last five sectors of the disc are excluded from checksums,
+/// because drives with different read offsets cannot all reach them.
+const SKIPPED_EDGE_SAMPLES: u32 = 5 * SAMPLES_PER_SECTOR;
+
+const RESPONSE_HEADER_SIZE: usize = 13;
+const RESPONSE_TRACK_SIZE: usize = 9;
+
+/// Identifiers used to look a disc up in the AccurateRip database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccurateRipDiscId {
+    /// Number of audio tracks on the disc.
+    pub audio_tracks: u8,
+    /// Sum of the audio track offsets and the lead-out offset.
+    pub id1: u32,
+    /// Sum of the audio track offsets weighted by track number.
+    pub id2: u32,
+    /// FreeDB/CDDB disc ID, see [`Toc::cddb_disc_id`].
+    pub cddb_id: u32,
+}
+
+impl AccurateRipDiscId {
+    /// URL of the database response for this disc.
+    pub fn url(&self) -> String {
+        format!(
+            "http://www.accuraterip.com/accuraterip/{:x}/{:x}/{:x}/dBAR-{:03}-{:08x}-{:08x}-{:08x}.bin",
+            self.id1 & 0xF,
+            (self.id1 >> 4) & 0xF,
+            (self.id1 >> 8) & 0xF,
+            self.audio_tracks,
+            self.id1,
+            self.id2,
+            self.cddb_id
+        )
+    }
+}
+
+impl Toc {
+    /// Compute the AccurateRip disc IDs of this TOC.
+    ///
+    /// Data tracks do not contribute to the IDs, but the lead-out is always
+    /// the final lead-out of the disc and is weighted by the number of audio
+    /// tracks plus one, like AccurateRip itself does for CD-Extra and
+    /// mixed-mode discs.
+    pub fn accuraterip_disc_id(&self) -> Result<AccurateRipDiscId, CdReaderError> {
+        let audio_tracks: Vec<_> = self.tracks.iter().filter(|track| track.is_audio).collect();
+        if audio_tracks.is_empty() {
+            return Err(CdReaderError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "TOC does not contain any audio tracks",
+            )));
+        };
+
+        let mut id1 = 0u32;
+        let mut id2 = 0u32;
+        for track in &audio_tracks {
+            id1 = id1.wrapping_add(track.start_lba);
+            id2 = id2.wrapping_add(track.start_lba.max(1).wrapping_mul(track.number.into()));
+        }
+        id1 = id1.wrapping_add(self.leadout_lba);
+        id2 = id2.wrapping_add(self.leadout_lba.wrapping_mul(audio_tracks.len() as u32 + 1));
+
+        Ok(AccurateRipDiscId {
+            audio_tracks: audio_tracks.len() as u8,
+            id1,
+            id2,
+            cddb_id: self.cddb_disc_id(),
+        })
+    }
+}
+
+/// AccurateRip checksums of a single track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccurateRipChecksums {
+    /// Original AccurateRip checksum.
+    pub v1: u32,
+    /// AccurateRip v2 checksum, which also covers the upper bits of each product.
+    pub v2: u32,
+}
+
+/// Incremental AccurateRip checksum over the PCM of one track.
+///
+/// Feed the track data in order, for example chunk by chunk from a
+/// [`TrackStream`](crate::TrackStream), then call [`finish`](Self::finish).
+#[derive(Debug, Clone)]
+pub struct AccurateRipHasher {
+    /// 1-based index of the next sample, which is also its multiplier.
+    position: u32,
+    check_from: u32,
+    check_to: u32,
+    v1: u32,
+    v2: u32,
+    partial: Vec<u8>,
+}
+
+impl AccurateRipHasher {
+    /// Create a hasher for a track of `total_samples` stereo samples.
+    ///
+    /// The first audio track of a disc skips its first five sectors (minus one
+    /// sample), and the last audio track skips its last five sectors.
+    pub fn new(total_samples: u32, is_first_track: bool, is_last_track: bool) -> Self {
+        let check_from = if is_first_track {
+            SKIPPED_EDGE_SAMPLES
+        } else {
+            1
+        };
+        let check_to = if is_last_track {
+            total_samples.saturating_sub(SKIPPED_EDGE_SAMPLES)
+        } else {
+            total_samples
+        };
+
+        Self {
+            position: 1,
+            check_from,
+            check_to,
+            v1: 0,
+            v2: 0,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Create a hasher for a TOC track, using the same boundaries as
+    /// [`CdReader::read_track`](crate::CdReader::read_track).
+    pub fn for_track(toc: &Toc, track_no: u8) -> Result<Self, CdReaderError> {
+        let (_, sectors) = utils::get_track_bounds(toc, track_no)?;
+        let (session_tracks, _) = utils::first_session_tracks(toc)?;
+        let mut audio_tracks = session_tracks.iter().filter(|track| track.is_audio);
+        let first = audio_tracks.next().map(|track| track.number);
+        let last = audio_tracks.next_back().map(|track| track.number).or(first);
+
+        Ok(Self::new(
+            sectors * SAMPLES_PER_SECTOR,
+            first == Some(track_no),
+            last == Some(track_no),
+        ))
+    }
+
+    /// Add the next part of the track's PCM data.
+    pub fn update(&mut self, pcm: &[u8]) {
+        let mut pcm = pcm;
+        if !self.partial.is_empty() {
+            let needed = BYTES_PER_SAMPLE - self.partial.len();
+            let take = needed.min(pcm.len());
+            self.partial.extend_from_slice(&pcm[..take]);
+            pcm = &pcm[take..];
+            if self.partial.len() < BYTES_PER_SAMPLE {
+                return;
+            }
+            let sample = std::mem::take(&mut self.partial);
+            self.add_sample(&sample);
+        }
+
+        let mut samples = pcm.chunks_exact(BYTES_PER_SAMPLE);
+        for sample in &mut samples {
+            self.add_sample(sample);
+        }
+        self.partial.extend_from_slice(samples.remainder());
+    }
+
+    /// Return the checksums of all data passed to [`update`](Self::update).
+    pub fn finish(self) -> AccurateRipChecksums {
+        AccurateRipChecksums {
+            v1: self.v1,
+            v2: self.v2,
+        }
+    }
+
+    fn add_sample(&mut self, bytes: &[u8]) {
+        let multiplier = self.position;
+        self.position = self.position.wrapping_add(1);
+        if multiplier < self.check_from || multiplier > self.check_to {
+            return;
+        }
+
+        let sample = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let product = u64::from(sample) * u64::from(multiplier);
+        self.v1 = self.v1.wrapping_add(product as u32);
+        self.v2 = self
+            .v2
+            .wrapping_add(product as u32)
+            .wrapping_add((product >> 32) as u32);
+    }
+}
+
+/// Compute the AccurateRip checksums of a complete track read with
+/// [`CdReader::read_track`](crate::CdReader::read_track).
+pub fn accuraterip_checksums(
+    toc: &Toc,
+    track_no: u8,
+    pcm: &[u8],
+) -> Result<AccurateRipChecksums, CdReaderError> {
+    let mut hasher = AccurateRipHasher::for_track(toc, track_no)?;
+    hasher.update(pcm);
+    Ok(hasher.finish())
+}
+
+/// One track entry of an AccurateRip database response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccurateRipTrackEntry {
+    /// Number of submissions that agree on this checksum.
+    pub confidence: u8,
+    /// Track checksum (v1 or v2, the response does not say which).
+    pub crc: u32,
+    /// Checksum of sector 450 of the track, used for offset detection.
+    pub frame450_crc: u32,
+}
+
+/// Checksums submitted for one pressing of the disc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccurateRipPressing {
+    /// Disc IDs stored with the entry.
+    pub disc_id: AccurateRipDiscId,
+    /// One entry per audio track, in disc order.
+    pub tracks: Vec<AccurateRipTrackEntry>,
+}
+
+/// Which checksum version matched a database entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccurateRipVersion {
+    /// Original checksum.
+    V1,
+    /// v2 checksum.
+    V2,
+}
+
+/// A database entry that matches a track's checksums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccurateRipMatch {
+    /// Checksum version that matched.
+    pub version: AccurateRipVersion,
+    /// Number of submissions with the same checksum.
+    pub confidence: u8,
+}
+
+/// Parsed `dBAR-*.bin` response from the AccurateRip database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccurateRipResponse {
+    /// Submitted pressings, in the order they appear in the response.
+    pub pressings: Vec<AccurateRipPressing>,
+}
+
+impl AccurateRipResponse {
+    /// Parse the binary database response.
+    pub fn parse(data: &[u8]) -> Result<Self, CdReaderError> {
+        let mut pressings = Vec::new();
+        let mut rest = data;
+
+        while !rest.is_empty() {
+            if rest.len() < RESPONSE_HEADER_SIZE {
+                return Err(truncated_response());
+            }
+            let audio_tracks = rest[0];
+            let disc_id = AccurateRipDiscId {
+                audio_tracks,
+                id1: read_u32_le(&rest[1..]),
+                id2: read_u32_le(&rest[5..]),
+                cddb_id: read_u32_le(&rest[9..]),
+            };
+            rest = &rest[RESPONSE_HEADER_SIZE..];
+
+            let tracks_size = usize::from(audio_tracks) * RESPONSE_TRACK_SIZE;
+            if rest.len() < tracks_size {
+                return Err(truncated_response());
+            }
+            let tracks = rest[..tracks_size]
+                .chunks_exact(RESPONSE_TRACK_SIZE)
+                .map(|entry| AccurateRipTrackEntry {
+                    confidence: entry[0],
+                    crc: read_u32_le(&entry[1..]),
+                    frame450_crc: read_u32_le(&entry[5..]),
+                })
+                .collect();
+            rest = &rest[tracks_size..];
+
+            pressings.push(AccurateRipPressing { disc_id, tracks });
+        }
+
+        Ok(Self { pressings })
+    }
+
+    /// Find the best-confidence entry matching a track's checksums.
+    ///
+    /// `track_index` is the 0-based position of the track among the disc's
+    /// audio tracks.
+    pub fn find_match(
+        &self,
+        track_index: usize,
+        checksums: &AccurateRipChecksums,
+    ) -> Option<AccurateRipMatch> {
+        self.pressings
+            .iter()
+            .filter_map(|pressing| pressing.tracks.get(track_index))
+            .filter_map(|entry| {
+                let version = if entry.crc == checksums.v2 {
+                    AccurateRipVersion::V2
+                } else if entry.crc == checksums.v1 {
+                    AccurateRipVersion::V1
+                } else {
+                    return None;
+                };
+                Some(AccurateRipMatch {
+                    version,
+                    confidence: entry.confidence,
+                })
+            })
+            .max_by_key(|found| found.confidence)
+    }
+}
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn truncated_response() -> CdReaderError {
+    CdReaderError::Parse("AccurateRip response is truncated".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{pcm, sample_toc, toc};
+
+    #[test]
+    fn computes_reference_checksums() {
+        let pcm = pcm(12);
+        let samples = 12 * SAMPLES_PER_SECTOR;
+        let checksum = |first, last| {
+            let mut hasher = AccurateRipHasher::new(samples, first, last);
+            hasher.update(&pcm);
+            hasher.finish()
+        };
+
+        assert_eq!(
+            checksum(true, true),
+            AccurateRipChecksums {
+                v1: 0xe2ab99ea,
+                v2: 0xe2cb7f6b
+            }
+        );
+        assert_eq!(
+            checksum(false, false),
+            AccurateRipChecksums {
+                v1: 0x44c250f8,
+                v2: 0x458fec8a
+            }
+        );
+        assert_eq!(
+            checksum(true, false),
+            AccurateRipChecksums {
+                v1: 0xf6aa7b22,
+                v2: 0xf75679d5
+            }
+        );
+    }
+
+    #[test]
+    fn streaming_updates_match_a_single_update() {
+        let pcm = pcm(12);
+        let mut whole = AccurateRipHasher::new(12 * SAMPLES_PER_SECTOR, true, true);
+        whole.update(&pcm);
+
+        let mut streamed = AccurateRipHasher::new(12 * SAMPLES_PER_SECTOR, true, true);
+        for chunk in pcm.chunks(1_001) {
+            streamed.update(chunk);
+        }
+
+        assert_eq!(streamed.finish(), whole.finish());
+    }
+
+    #[test]
+    fn computes_disc_ids_and_url() {
+        let disc_id = sample_toc().accuraterip_disc_id().unwrap();
+
+        assert_eq!(
+            disc_id,
+            AccurateRipDiscId {
+                audio_tracks: 11,
+                id1: 0x0011b88a,
+                id2: 0x009c2abf,
+                cddb_id: 0x990aab0b,
+            }
+        );
+        assert_eq!(
+            disc_id.url(),
+            "http://www.accuraterip.com/accuraterip/a/8/8/dBAR-011-0011b88a-009c2abf-990aab0b.bin"
+        );
+    }
+
+    #[test]
+    fn excludes_data_tracks_but_keeps_the_disc_leadout() {
+        let toc = toc(
+            &[
+                (1, 0, true),
+                (2, 10_000, true),
+                (3, 20_000, true),
+                (4, 41_400, false),
+            ],
+            60_000,
+        );
+        let disc_id = toc.accuraterip_disc_id().unwrap();
+
+        assert_eq!(disc_id.audio_tracks, 3);
+        assert_eq!(disc_id.id1, 0x00015f90);
+        assert_eq!(disc_id.id2, 0x0004e201);
+        assert_eq!(disc_id.cddb_id, toc.cddb_disc_id());
+    }
+
+    #[test]
+    fn weights_the_leadout_by_audio_tracks_on_mixed_mode_discs() {
+        let toc = toc(
+            &[(1, 0, false), (2, 15_000, true), (3, 30_000, true)],
+            50_000,
+        );
+        let disc_id = toc.accuraterip_disc_id().unwrap();
+
+        assert_eq!(disc_id.audio_tracks, 2);
+        assert_eq!(disc_id.id1, 0x00017318);
+        // 15 000 * 2 + 30 000 * 3 + 50 000 * 3
+        assert_eq!(disc_id.id2, 0x00041eb0);
+    }
+
+    #[test]
+    fn hasher_for_track_skips_edges_of_the_audio_session() {
+        let toc = toc(&[(1, 0, true), (2, 20, true), (3, 11_440, false)], 12_000);
+        let pcm = pcm(20);
+
+        let first = accuraterip_checksums(&toc, 1, &pcm).unwrap();
+        let mut expected = AccurateRipHasher::new(20 * SAMPLES_PER_SECTOR, true, false);
+        expected.update(&pcm);
+        assert_eq!(first, expected.finish());
+
+        let hasher = AccurateRipHasher::for_track(&toc, 2).unwrap();
+        assert_eq!(hasher.check_from, 1);
+        assert_eq!(
+            hasher.check_to,
+            20 * SAMPLES_PER_SECTOR - SKIPPED_EDGE_SAMPLES
+        );
+    }
+
+    #[test]
+    fn parses_responses_and_finds_matches() {
+        let mut data = Vec::new();
+        for (confidence, crc) in [(3u8, 0x1111_1111u32), (12, 0x2222_2222)] {
+            data.push(2);
+            data.extend_from_slice(&0x0011b88au32.to_le_bytes());
+            data.extend_from_slice(&0x009c2abfu32.to_le_bytes());
+            data.extend_from_slice(&0x990aab0bu32.to_le_bytes());
+            for track in 0..2u32 {
+                data.push(confidence);
+                data.extend_from_slice(&(crc + track).to_le_bytes());
+                data.extend_from_slice(&0xABCDu32.to_le_bytes());
+            }
+        }
+
+        let response = AccurateRipResponse::parse(&data).unwrap();
+        assert_eq!(response.pressings.len(), 2);
+        assert_eq!(response.pressings[0].disc_id.cddb_id, 0x990aab0b);
+        assert_eq!(
+            response.pressings[1].tracks[1],
+            AccurateRipTrackEntry {
+                confidence: 12,
+                crc: 0x2222_2223,
+                frame450_crc: 0xABCD,
+            }
+        );
+
+        let checksums = AccurateRipChecksums {
+            v1: 0x1111_1112,
+            v2: 0x2222_2223,
+        };
+        assert_eq!(
+            response.find_match(1, &checksums),
+            Some(AccurateRipMatch {
+                version: AccurateRipVersion::V2,
+                confidence: 12,
+            })
+        );
+        assert_eq!(
+            response.find_match(
+                0,
+                &AccurateRipChecksums {
+                    v1: 0x1111_1111,
+                    v2: 0
+                }
+            ),
+            Some(AccurateRipMatch {
+                version: AccurateRipVersion::V1,
+                confidence: 3,
+            })
+        );
+        assert_eq!(response.find_match(5, &checksums), None);
+
+        data.pop();
+        assert!(AccurateRipResponse::parse(&data).is_err());
+    }
+}
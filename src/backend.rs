@@ -0,0 +1,199 @@
+use crate::{
+    CdReaderError, DriveCapabilities, InquiryData, MediaEventStatus, MediaState, ReadSpeed,
+    SectorReadFormat, Toc, TrackInformation,
+};
+
+/// Transport used by [`CdReader`](crate::CdReader) to talk to a drive.
+///
+/// The platform drive opened by [`CdReader::open_path`](crate::CdReader::open_path)
+/// implements this trait, and so can any other source of disc data: a disc image,
+/// a network transport or a test double. Wrap it with
+/// [`CdReader::from_backend`](crate::CdReader::from_backend) to use the whole
+/// reading API (retries, streaming, format detection) on top of it.
+///
+/// Each method corresponds to a single drive command. Retries, chunking and
+/// track boundaries are handled by `CdReader`, so implementations should not
+/// retry on their own. Methods with a default implementation describe drive
+/// features a backend may not have; the default reports them as unsupported.
+pub trait DriveBackend: Send + Sync {
+    /// Read the Table of Contents of the inserted disc.
+    fn read_toc(&self) -> Result<Toc, CdReaderError>;
+
+    /// Read MMC track information for a track number from the TOC.
+    fn read_track_information(&self, track_number: u8) -> Result<TrackInformation, CdReaderError>;
+
+    /// Issue one `READ CD` command for `sectors` sectors starting at `lba`.
+    ///
+    /// The returned buffer should hold `sectors * format.sector_size()` bytes.
+    /// A shorter buffer is treated as a failed read and retried.
+    fn read_cd_chunk(
+        &self,
+        lba: u32,
+        sectors: u32,
+        format: SectorReadFormat,
+    ) -> Result<Vec<u8>, CdReaderError>;
+
+    /// Identify the drive with a standard INQUIRY command.
+    fn inquiry(&self) -> Result<InquiryData, CdReaderError> {
+        Err(unsupported("INQUIRY"))
+    }
+
+    /// Read the drive's capabilities with MODE SENSE (page `0x2A`) and
+    /// GET CONFIGURATION.
+    fn capabilities(&self) -> Result<DriveCapabilities, CdReaderError> {
+        Err(unsupported("MODE SENSE"))
+    }
+
+    /// Open the tray, or eject the disc from a slot-loading drive.
+    fn eject(&self) -> Result<(), CdReaderError> {
+        Err(unsupported("eject"))
+    }
+
+    /// Close the tray.
+    fn load(&self) -> Result<(), CdReaderError> {
+        Err(unsupported("load"))
+    }
+
+    /// Prevent (`true`) or allow (`false`) removing the disc.
+    fn set_tray_locked(&self, locked: bool) -> Result<(), CdReaderError> {
+        let _ = locked;
+        Err(unsupported("tray locking"))
+    }
+
+    /// Ask the drive to read at the given speed.
+    fn set_read_speed(&self, speed: ReadSpeed) -> Result<(), CdReaderError> {
+        let _ = speed;
+        Err(unsupported("SET CD SPEED"))
+    }
+
+    /// Check whether a readable disc is loaded with TEST UNIT READY.
+    fn media_state(&self) -> Result<MediaState, CdReaderError> {
+        Err(unsupported("TEST UNIT READY"))
+    }
+
+    /// Poll for a media event with GET EVENT STATUS NOTIFICATION.
+    fn poll_media_event(&self) -> Result<MediaEventStatus, CdReaderError> {
+        Err(unsupported("GET EVENT STATUS NOTIFICATION"))
+    }
+
+    /// Make the drive drop its cached sectors by reading the block at `lba`
+    /// with READ(10) and Force Unit Access.
+    fn invalidate_cache(&self, lba: u32) -> Result<(), CdReaderError> {
+        let _ = lba;
+        Err(unsupported("READ(10) with FUA"))
+    }
+}
+
+/// Error returned for commands a backend does not implement.
+pub(crate) fn unsupported(command: &str) -> CdReaderError {
+    CdReaderError::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("{command} is not supported by this drive backend"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DriveBackend;
+    use crate::{CdReader, CdReaderError, SectorReadFormat, Toc, Track, TrackInformation};
+
+    struct FakeBackend;
+
+    impl DriveBackend for FakeBackend {
+        fn read_toc(&self) -> Result<Toc, CdReaderError> {
+            Ok(Toc {
+                first_track: 1,
+                last_track: 2,
+                tracks: vec![
+                    Track {
+                        number: 1,
+                        start_lba: 0,
+                        start_msf: (0, 2, 0),
+                        is_audio: false,
+                    },
+                    Track {
+                        number: 2,
+                        start_lba: 60,
+                        start_msf: (0, 2, 60),
+                        is_audio: true,
+                    },
+                ],
+                leadout_lba: 100,
+            })
+        }
+
+        fn read_track_information(
+            &self,
+            track_number: u8,
+        ) -> Result<TrackInformation, CdReaderError> {
+            Ok(TrackInformation {
+                track_number: track_number.into(),
+                session_number: 1,
+                track_mode: 4,
+                data_mode: 1,
+                start_lba: 0,
+                track_size: 60,
+            })
+        }
+
+        fn read_cd_chunk(
+            &self,
+            lba: u32,
+            sectors: u32,
+            format: SectorReadFormat,
+        ) -> Result<Vec<u8>, CdReaderError> {
+            Ok(vec![lba as u8; sectors as usize * format.sector_size()])
+        }
+    }
+
+    fn reader() -> CdReader {
+        CdReader::from_backend(FakeBackend)
+    }
+
+    #[test]
+    fn reads_tracks_through_a_custom_backend() {
+        let reader = reader();
+        let toc = reader.read_toc().unwrap();
+
+        let data = reader.read_track(&toc, 2).unwrap();
+
+        assert_eq!(data.len(), 40 * 2352);
+        assert!(data[..27 * 2352].iter().all(|byte| *byte == 60));
+        assert!(data[27 * 2352..].iter().all(|byte| *byte == 87));
+    }
+
+    #[test]
+    fn detects_data_track_format_through_a_custom_backend() {
+        let reader = reader();
+        let toc = reader.read_toc().unwrap();
+
+        assert_eq!(
+            reader.detect_track_format(&toc.tracks[0]).unwrap(),
+            SectorReadFormat::Mode1Cooked
+        );
+    }
+
+    #[test]
+    fn streams_tracks_through_a_custom_backend() {
+        let reader = reader();
+        let toc = reader.read_toc().unwrap();
+        let mut stream = reader.open_track_stream(&toc, 2).unwrap();
+
+        let mut total = 0;
+        while let Some(chunk) = stream.next_chunk().unwrap() {
+            total += chunk.len();
+        }
+
+        assert_eq!(total, 40 * 2352);
+    }
+
+    #[test]
+    fn optional_commands_default_to_unsupported() {
+        match reader().inquiry() {
+            Err(CdReaderError::Io(error)) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::Unsupported)
+            }
+            other => panic!("expected Unsupported, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::io;
+
+use crate::CdReaderError;
+
+/// Size of the C2 error pointers returned after each sector's audio: one
+/// bit per audio byte.
+pub(crate) const C2_POINTERS_SIZE: usize = 294;
+
+const AUDIO_SECTOR_SIZE: usize = 2352;
+const BYTES_PER_SAMPLE: usize = 4;
+const SAMPLES_PER_SECTOR: usize = AUDIO_SECTOR_SIZE / BYTES_PER_SAMPLE;
+
+/// Audio read with [`SectorReadFormat::AudioWithC2`](crate::SectorReadFormat::AudioWithC2),
+/// split into PCM and the drive's C2 error pointers.
+///
+/// A C2 error means the drive's last error correction stage could not fix a
+/// byte, so the drive interpolated or guessed it. Flagged samples are
+/// suspect; unflagged ones are almost always right, which makes C2 a cheap
+/// alternative to comparing several reads.
+///
+/// ```no_run
+/// use cd_da_reader::{C2Read, CdReader, ReadOptions, SectorReadFormat};
+///
+/// let reader = CdReader::open_default()?;
+/// let toc = reader.read_toc()?;
+/// let options = ReadOptions::default().with_format(SectorReadFormat::AudioWithC2);
+/// let read = C2Read::from_sectors(&reader.read_track_with_options(&toc, 1, &options)?)?;
+/// for sector in read.erroneous_sectors() {
+///     println!("sector {sector} has C2 errors");
+/// }
+/// let pcm = read.into_audio();
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct C2Read {
+    audio: Vec<u8>,
+    pointers: Vec<u8>,
+}
+
+impl C2Read {
+    /// Split data read with
+    /// [`SectorReadFormat::AudioWithC2`](crate::SectorReadFormat::AudioWithC2),
+    /// 2646 bytes per sector.
+    pub fn from_sectors(data: &[u8]) -> Result<Self, CdReaderError> {
+        let sector_size = AUDIO_SECTOR_SIZE + C2_POINTERS_SIZE;
+        if !data.len().is_multiple_of(sector_size) {
+            return Err(CdReaderError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("C2 read is not a whole number of {sector_size}-byte sectors"),
+            )));
+        }
+
+        let sectors = data.len() / sector_size;
+        let mut audio = Vec::with_capacity(sectors * AUDIO_SECTOR_SIZE);
+        let mut pointers = Vec::with_capacity(sectors * C2_POINTERS_SIZE);
+        for sector in data.chunks_exact(sector_size) {
+            let (sector_audio, sector_pointers) = sector.split_at(AUDIO_SECTOR_SIZE);
+            audio.extend_from_slice(sector_audio);
+            pointers.extend_from_slice(sector_pointers);
+        }
+
+        Ok(Self { audio, pointers })
+    }
+
+    /// The PCM audio, 2352 bytes per sector.
+    pub fn audio(&self) -> &[u8] {
+        &self.audio
+    }
+
+    /// Take the PCM audio, dropping the error pointers.
+    pub fn into_audio(self) -> Vec<u8> {
+        self.audio
+    }
+
+    /// Number of sectors read.
+    pub fn sectors(&self) -> usize {
+        self.pointers.len() / C2_POINTERS_SIZE
+    }
+
+    /// The raw C2 bitmap of a sector: the most significant bit of the first
+    /// byte flags the sector's first audio byte.
+    pub fn sector_pointers(&self, sector: usize) -> &[u8] {
+        &self.pointers[sector * C2_POINTERS_SIZE..(sector + 1) * C2_POINTERS_SIZE]
+    }
+
+    /// Whether any byte of a sector, counted from the start of the read, has
+    /// a C2 error.
+    pub fn sector_has_errors(&self, sector: usize) -> bool {
+        self.sector_pointers(sector).iter().any(|&bits| bits != 0)
+    }
+
+    /// Whether a stereo sample (4 bytes), counted from the start of the
+    /// read, has a C2 error in any of its bytes.
+    pub fn sample_has_error(&self, sample: usize) -> bool {
+        // four bits per sample, so two samples per pointer byte
+        let bits = self.pointers[sample / 2];
+        let nibble = if sample.is_multiple_of(2) {
+            bits >> 4
+        } else {
+            bits
+        };
+        nibble & 0x0F != 0
+    }
+
+    /// Indices of the sectors with C2 errors.
+    pub fn erroneous_sectors(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.sectors()).filter(|&sector| self.sector_has_errors(sector))
+    }
+
+    /// Indices of the stereo samples with C2 errors.
+    pub fn erroneous_samples(&self) -> impl Iterator<Item = usize> + '_ {
+        self.erroneous_sectors().flat_map(move |sector| {
+            let first = sector * SAMPLES_PER_SECTOR;
+            (first..first + SAMPLES_PER_SECTOR).filter(|&sample| self.sample_has_error(sample))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::C2Read;
+    use crate::{CdReader, MockDrive, MockFault, ReadOptions, SectorReadFormat};
+
+    fn sector(fill: u8, pointers: &[(usize, u8)]) -> Vec<u8> {
+        let mut data = vec![fill; 2352];
+        let mut c2 = vec![0u8; 294];
+        for &(idx, bits) in pointers {
+            c2[idx] = bits;
+        }
+        data.extend_from_slice(&c2);
+        data
+    }
+
+    #[test]
+    fn splits_audio_and_error_pointers() {
+        let data = [sector(1, &[]), sector(2, &[(0, 0x01), (293, 0x80)])].concat();
+        let read = C2Read::from_sectors(&data).unwrap();
+
+        assert_eq!(read.sectors(), 2);
+        assert_eq!(read.audio(), [vec![1; 2352], vec![2; 2352]].concat());
+        assert!(!read.sector_has_errors(0));
+        assert!(read.sector_has_errors(1));
+        assert_eq!(read.erroneous_sectors().collect::<Vec<_>>(), vec![1]);
+        // 0x01 flags the last byte of the sector's second sample, 0x80 the
+        // first byte of its second-to-last
+        assert_eq!(
+            read.erroneous_samples().collect::<Vec<_>>(),
+            vec![589, 1174]
+        );
+        assert!(C2Read::from_sectors(&data[..2352]).is_err());
+    }
+
+    #[test]
+    fn reads_c2_pointers_from_a_drive() {
+        let drive = MockDrive::audio_cd(&[10]);
+        drive.fail_lba(4, [MockFault::Corrupt]);
+        let reader = CdReader::from_backend(drive);
+        let toc = reader.read_toc().unwrap();
+        let options = ReadOptions::default().with_format(SectorReadFormat::AudioWithC2);
+
+        let data = reader.read_track_with_options(&toc, 1, &options).unwrap();
+        let read = C2Read::from_sectors(&data).unwrap();
+
+        assert_eq!(read.erroneous_sectors().collect::<Vec<_>>(), vec![4]);
+        assert_eq!(read.erroneous_samples().count(), 588);
+        assert_eq!(
+            read.audio()[..2352],
+            MockDrive::sector_data(0, SectorReadFormat::Audio)
+        );
+    }
+}
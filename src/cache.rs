@@ -0,0 +1,300 @@
+//! Defeating the drive's read cache for verification re-reads.
+//!
+//! Drives keep recently read sectors in a buffer, so reading a sector again
+//! right away usually returns the buffered copy instead of reading the disc,
+//! including any errors the drive concealed the first time. Comparing such
+//! re-reads proves nothing unless the cached copy is gone first.
+
+use crate::data_reader::SectorReadFormat;
+use crate::{CdReader, CdReaderError, utils};
+
+/// Cache size assumed when the drive does not report its buffer size, larger
+/// than the cache of most drives.
+const DEFAULT_CACHE_SIZE_KB: u32 = 4096;
+
+/// How to make the drive read sectors from the disc again instead of
+/// returning its cached copy, see [`ReadOptions::with_cache_defeat`](crate::ReadOptions::with_cache_defeat).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheDefeat {
+    /// Read one sector at the far end of the audio session first. Enough for
+    /// drives that only cache what they read ahead of the last request.
+    Seek,
+    /// Read as many sectors as the drive's cache holds, far from the sectors
+    /// being read, so that they are evicted. The cache size is the buffer
+    /// size from MODE SENSE, or 4 MB when the drive does not report it. This
+    /// works on any drive but is slow.
+    Evict,
+    /// Read one block with READ(10) and Force Unit Access, which makes drives
+    /// that support it drop their cache. Fast, but many drives ignore it.
+    /// Where the command fails, including on macOS, [`CacheDefeat::Evict`]
+    /// is used instead.
+    ForceUnitAccess,
+}
+
+/// Buffer for the one block READ(10) transfers, large enough for both the
+/// 2048-byte data and 2352-byte audio block sizes drives use.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub(crate) const FUA_READ_BUFFER_SIZE: usize = 2352;
+
+/// Build a READ(10) (0x28) command descriptor block with the FUA bit set for
+/// one block at `lba`.
+pub(crate) fn build_read10_fua_cdb(lba: u32) -> [u8; 10] {
+    let mut cdb = [0u8; 10];
+    cdb[0] = 0x28;
+    cdb[1] = 0x08; // FUA
+    cdb[2..6].copy_from_slice(&lba.to_be_bytes());
+    cdb[7..9].copy_from_slice(&1u16.to_be_bytes());
+    cdb
+}
+
+/// Pick up to `count` sectors below `disc_end` outside `lba..lba + sectors`,
+/// starting from the edge of the disc farther from it. Returns up to two
+/// `(start, sectors)` ranges, the second one used only when the far side
+/// is smaller than `count`.
+fn eviction_ranges(lba: u32, sectors: u32, count: u32, disc_end: u32) -> [(u32, u32); 2] {
+    let start = lba.min(disc_end);
+    let end = lba.saturating_add(sectors).min(disc_end);
+    let before = start;
+    let after = disc_end - end;
+
+    if before >= after {
+        let first = count.min(before);
+        let second = (count - first).min(after);
+        [(0, first), (disc_end - second, second)]
+    } else {
+        let first = count.min(after);
+        let second = (count - first).min(before);
+        [(disc_end - first, first), (0, second)]
+    }
+}
+
+/// Cache defeat for the reads of one secure read, with what it needs from
+/// the drive looked up once.
+pub(crate) struct CacheDefeater<'a> {
+    reader: &'a CdReader,
+    defeat: CacheDefeat,
+    format: SectorReadFormat,
+    audio_end_lba: u32,
+    evict_sectors: u32,
+}
+
+impl CdReader {
+    /// Prepare to defeat the cache with `defeat`. Without `audio_end_lba`,
+    /// the end of the audio session is read from the TOC.
+    pub(crate) fn cache_defeater(
+        &self,
+        defeat: CacheDefeat,
+        format: SectorReadFormat,
+        audio_end_lba: Option<u32>,
+    ) -> Result<CacheDefeater<'_>, CdReaderError> {
+        let audio_end_lba = match audio_end_lba {
+            Some(lba) => lba,
+            None => utils::first_session_tracks(&self.drive.read_toc()?)?.1,
+        };
+        let evict_sectors = match defeat {
+            CacheDefeat::Evict => self.cache_sectors(),
+            CacheDefeat::Seek | CacheDefeat::ForceUnitAccess => 0,
+        };
+
+        Ok(CacheDefeater {
+            reader: self,
+            defeat,
+            format,
+            audio_end_lba,
+            evict_sectors,
+        })
+    }
+
+    fn cache_sectors(&self) -> u32 {
+        self.drive
+            .capabilities()
+            .ok()
+            .and_then(|capabilities| capabilities.cache_sectors())
+            .unwrap_or((DEFAULT_CACHE_SIZE_KB * 1024).div_ceil(2352))
+    }
+}
+
+impl CacheDefeater<'_> {
+    /// Make the drive forget any cached copy of `lba..lba + sectors`.
+    ///
+    /// This is best effort: reads done only to move the head or fill the
+    /// cache may fail, for example on sectors that are not in the read
+    /// format, and are ignored. When Force Unit Access fails, for example on
+    /// drives that reject READ(10) on audio sectors, the rest of the read
+    /// evicts the cache instead.
+    pub(crate) fn defeat(&mut self, lba: u32, sectors: u32) {
+        let count = match self.defeat {
+            CacheDefeat::ForceUnitAccess => {
+                if self.reader.drive.invalidate_cache(lba).is_ok() {
+                    return;
+                }
+                self.defeat = CacheDefeat::Evict;
+                self.evict_sectors = self.reader.cache_sectors();
+                self.evict_sectors
+            }
+            CacheDefeat::Seek => 1,
+            CacheDefeat::Evict => self.evict_sectors,
+        };
+
+        for (mut from, count) in eviction_ranges(lba, sectors, count, self.audio_end_lba) {
+            let end = from + count;
+            while from < end {
+                let chunk = (end - from).min(self.format.max_sectors_per_xfer());
+                let _ = self.reader.drive.read_cd_chunk(from, chunk, self.format);
+                from += chunk;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheDefeat, build_read10_fua_cdb, eviction_ranges};
+    use crate::{
+        CdReader, CdReaderError, DriveBackend, DriveCapabilities, MockDrive, MockFault,
+        ReadOptions, SectorReadFormat, SecureReadConfig, Toc, TrackInformation,
+    };
+
+    /// Mock drive rejecting READ(10) with FUA, like the default backend.
+    struct WithoutFua(MockDrive);
+
+    impl DriveBackend for WithoutFua {
+        fn read_toc(&self) -> Result<Toc, CdReaderError> {
+            self.0.read_toc()
+        }
+
+        fn read_track_information(
+            &self,
+            track_number: u8,
+        ) -> Result<TrackInformation, CdReaderError> {
+            self.0.read_track_information(track_number)
+        }
+
+        fn read_cd_chunk(
+            &self,
+            lba: u32,
+            sectors: u32,
+            format: SectorReadFormat,
+        ) -> Result<Vec<u8>, CdReaderError> {
+            self.0.read_cd_chunk(lba, sectors, format)
+        }
+    }
+
+    #[test]
+    fn builds_force_unit_access_read() {
+        assert_eq!(
+            build_read10_fua_cdb(0x0102_0304),
+            [0x28, 0x08, 0x01, 0x02, 0x03, 0x04, 0, 0, 0x01, 0]
+        );
+    }
+
+    #[test]
+    fn evicts_from_the_far_side_of_the_disc() {
+        assert_eq!(eviction_ranges(10, 5, 1, 1000), [(999, 1), (0, 0)]);
+        assert_eq!(eviction_ranges(900, 5, 300, 1000), [(0, 300), (1000, 0)]);
+        assert_eq!(eviction_ranges(400, 10, 800, 1000), [(410, 590), (0, 210)]);
+    }
+
+    #[test]
+    fn never_evicts_with_the_sectors_being_read() {
+        for (lba, sectors) in [(0, 10), (500, 20), (990, 10), (995, 10)] {
+            let ranges = eviction_ranges(lba, sectors, 5000, 1000);
+            for (start, count) in ranges {
+                assert!(
+                    start + count <= lba || start >= lba + sectors || count == 0,
+                    "{ranges:?} overlaps {lba}..{}",
+                    lba + sectors
+                );
+            }
+            let total: u32 = ranges.iter().map(|(_, count)| count).sum();
+            assert_eq!(total, 1000 - sectors.min(1000 - lba.min(1000)));
+        }
+    }
+
+    #[test]
+    fn secure_reads_only_catch_corruption_without_the_cache() {
+        let corrupted_drive = |defeat: Option<CacheDefeat>| {
+            let drive = MockDrive::audio_cd(&[2000]);
+            drive.set_read_cache(100);
+            drive.set_capabilities(DriveCapabilities {
+                buffer_size: Some(256),
+                ..DriveCapabilities::default()
+            });
+            drive.fail_lba(5, [MockFault::Corrupt]);
+            let reader = CdReader::from_backend(drive.clone());
+            let mut options = ReadOptions::default();
+            if let Some(defeat) = defeat {
+                options = options.with_cache_defeat(defeat);
+            }
+            let read = reader
+                .read_sector_range_secure(0, 10, &options, &SecureReadConfig::default())
+                .unwrap();
+            read.data[5 * 2352..6 * 2352] == MockDrive::sector_data(5, SectorReadFormat::Audio)
+        };
+
+        // the second pass is served from the cache and confirms the bad data
+        assert!(!corrupted_drive(None));
+        assert!(corrupted_drive(Some(CacheDefeat::Evict)));
+        assert!(corrupted_drive(Some(CacheDefeat::ForceUnitAccess)));
+    }
+
+    #[test]
+    fn evicts_once_per_round_of_rereads() {
+        let drive = MockDrive::audio_cd(&[2000]);
+        drive.set_capabilities(DriveCapabilities {
+            buffer_size: Some(64),
+            ..DriveCapabilities::default()
+        });
+        drive.fail_lba(5, [MockFault::Corrupt]);
+        drive.fail_lba(7, [MockFault::Corrupt]);
+        let reader = CdReader::from_backend(drive.clone());
+        let options = ReadOptions::default().with_cache_defeat(CacheDefeat::Evict);
+
+        let read = reader
+            .read_sector_range_secure(0, 10, &options, &SecureReadConfig::default())
+            .unwrap();
+
+        assert!(read.is_secure());
+        let commands = drive.commands();
+        let evictions = commands.iter().filter(|command| command.lba >= 10).count();
+        // 28 sectors in two chunks after the first pass and before the
+        // single round re-reading 5 and 7
+        assert_eq!(evictions, 4);
+    }
+
+    #[test]
+    fn falls_back_to_eviction_without_force_unit_access() {
+        let drive = MockDrive::audio_cd(&[2000]);
+        drive.set_read_cache(100);
+        drive.fail_lba(5, [MockFault::Corrupt]);
+        let reader = CdReader::from_backend(WithoutFua(drive));
+        let options = ReadOptions::default().with_cache_defeat(CacheDefeat::ForceUnitAccess);
+
+        let read = reader
+            .read_sector_range_secure(0, 10, &options, &SecureReadConfig::default())
+            .unwrap();
+
+        assert_eq!(
+            read.data[5 * 2352..6 * 2352],
+            MockDrive::sector_data(5, SectorReadFormat::Audio)
+        );
+    }
+
+    #[test]
+    fn seeks_to_the_far_end_of_the_disc() {
+        let drive = MockDrive::audio_cd(&[500, 500]);
+        let reader = CdReader::from_backend(drive.clone());
+        let options = ReadOptions::default().with_cache_defeat(CacheDefeat::Seek);
+
+        reader
+            .read_sector_range_secure(10, 2, &options, &SecureReadConfig::default())
+            .unwrap();
+
+        let commands: Vec<_> = drive
+            .commands()
+            .iter()
+            .map(|command| (command.lba, command.sectors))
+            .collect();
+        assert_eq!(commands, vec![(10, 2), (999, 1), (10, 2)]);
+    }
+}
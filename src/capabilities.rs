@@ -0,0 +1,258 @@
+use std::io;
+
+/// Allocation length for MODE SENSE(10): the mode parameter header plus the
+/// capabilities page, including its write speed descriptors.
+pub(crate) const MODE_SENSE_RESPONSE_SIZE: usize = 256;
+
+/// Allocation length for GET CONFIGURATION with a single requested feature.
+pub(crate) const GET_CONFIGURATION_RESPONSE_SIZE: usize = 32;
+
+const CAPABILITIES_PAGE: u8 = 0x2A;
+const CD_READ_FEATURE: u16 = 0x001E;
+
+/// What the drive reports it can do, from the CD/DVD capabilities mode page
+/// (`0x2A`) and the MMC "CD Read" feature.
+///
+/// These are the drive's own claims; some drives, for example, report
+/// accurate streaming without actually providing it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DriveCapabilities {
+    /// CD-DA commands (`READ CD` on audio sectors) are supported.
+    pub cd_da_commands: bool,
+    /// An audio read can resume at the exact position where a previous one
+    /// stopped, without jitter between commands.
+    pub accurate_stream: bool,
+    /// `READ CD` can return C2 error pointers along with audio data.
+    pub c2_pointers: bool,
+    /// Raw P-W subchannel data can be returned with `READ CD`.
+    pub raw_subchannel: bool,
+    /// R-W subchannel data can be returned de-interleaved and error corrected.
+    pub corrected_subchannel: bool,
+    /// ISRC codes can be read from the formatted Q subchannel.
+    pub isrc: bool,
+    /// The media catalog number (UPC/EAN) can be read from the formatted Q
+    /// subchannel.
+    pub media_catalog_number: bool,
+    /// CD-TEXT can be read with `READ TOC/PMA/ATIP`.
+    pub cd_text: bool,
+    /// R-W subchannel data in the lead-in can be read, which is where
+    /// CD-TEXT is stored.
+    pub lead_in: bool,
+    /// Maximum read speed in kB/s (176 kB/s is 1x), when reported.
+    pub max_read_speed: Option<u16>,
+    /// Current read speed in kB/s, when reported.
+    pub current_read_speed: Option<u16>,
+    /// Maximum write speed in kB/s, when reported. `None` for read-only
+    /// drives.
+    pub max_write_speed: Option<u16>,
+    /// Size of the drive's read buffer in kB, when reported.
+    pub buffer_size: Option<u16>,
+}
+
+impl DriveCapabilities {
+    /// Number of audio sectors that fit in the drive's read buffer, which
+    /// is how much it can cache.
+    pub fn cache_sectors(&self) -> Option<u32> {
+        self.buffer_size
+            .map(|size| (u32::from(size) * 1024).div_ceil(2352))
+    }
+}
+
+/// Build a MODE SENSE(10) (0x5A) command descriptor block for the current
+/// values of the capabilities page, without block descriptors.
+pub(crate) fn build_mode_sense_capabilities_cdb(allocation_length: u16) -> [u8; 10] {
+    let mut cdb = [0u8; 10];
+    cdb[0] = 0x5A;
+    cdb[1] = 0x08; // DBD
+    cdb[2] = CAPABILITIES_PAGE;
+    cdb[7..9].copy_from_slice(&allocation_length.to_be_bytes());
+    cdb
+}
+
+/// Build a GET CONFIGURATION (0x46) command descriptor block that requests
+/// only the "CD Read" feature descriptor.
+pub(crate) fn build_get_configuration_cdb(allocation_length: u16) -> [u8; 10] {
+    let mut cdb = [0u8; 10];
+    cdb[0] = 0x46;
+    cdb[1] = 0x02; // RT: only the feature in the starting feature number
+    cdb[2..4].copy_from_slice(&CD_READ_FEATURE.to_be_bytes());
+    cdb[7..9].copy_from_slice(&allocation_length.to_be_bytes());
+    cdb
+}
+
+/// Parse a MODE SENSE(10) response holding the capabilities page.
+pub(crate) fn parse_capabilities_page(data: &[u8]) -> io::Result<DriveCapabilities> {
+    const HEADER_SIZE: usize = 8;
+    // up to and including the buffer size field
+    const MINIMUM_PAGE_SIZE: usize = 14;
+
+    if data.len() < HEADER_SIZE {
+        return Err(invalid("MODE SENSE response is too short"));
+    }
+    let block_descriptors = u16::from_be_bytes([data[6], data[7]]) as usize;
+    let page = data
+        .get(HEADER_SIZE + block_descriptors..)
+        .filter(|page| page.len() >= 2)
+        .ok_or_else(|| invalid("MODE SENSE response has no capabilities page"))?;
+    if page[0] & 0x3F != CAPABILITIES_PAGE {
+        return Err(invalid("MODE SENSE returned an unexpected page"));
+    }
+
+    // the page length excludes the two byte page header
+    let page = &page[..page.len().min(page[1] as usize + 2)];
+    if page.len() < MINIMUM_PAGE_SIZE {
+        return Err(invalid("capabilities page is too short"));
+    }
+    let speed = |offset: usize| {
+        page.get(offset..offset + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .filter(|speed| *speed != 0)
+    };
+
+    Ok(DriveCapabilities {
+        cd_da_commands: page[5] & 0x01 != 0,
+        accurate_stream: page[5] & 0x02 != 0,
+        raw_subchannel: page[5] & 0x04 != 0,
+        corrected_subchannel: page[5] & 0x08 != 0,
+        c2_pointers: page[5] & 0x10 != 0,
+        isrc: page[5] & 0x20 != 0,
+        media_catalog_number: page[5] & 0x40 != 0,
+        lead_in: page[7] & 0x20 != 0,
+        // without the CD Read feature, reading R-W in the lead-in is the
+        // closest indication of CD-TEXT support
+        cd_text: page[7] & 0x20 != 0,
+        max_read_speed: speed(8),
+        buffer_size: speed(12),
+        current_read_speed: speed(14),
+        max_write_speed: speed(18),
+    })
+}
+
+/// Refine capabilities with the "CD Read" feature from a GET CONFIGURATION
+/// response. Responses without that feature leave them unchanged.
+pub(crate) fn apply_cd_read_feature(capabilities: &mut DriveCapabilities, data: &[u8]) {
+    const HEADER_SIZE: usize = 8;
+
+    let Some(descriptor) = data.get(HEADER_SIZE..HEADER_SIZE + 5) else {
+        return;
+    };
+    if u16::from_be_bytes([descriptor[0], descriptor[1]]) != CD_READ_FEATURE {
+        return;
+    }
+
+    capabilities.cd_text = descriptor[4] & 0x01 != 0;
+    capabilities.c2_pointers |= descriptor[4] & 0x02 != 0;
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CdReader, MockDrive};
+
+    fn mode_sense_response(page: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 8];
+        data[0..2].copy_from_slice(&((page.len() + 6) as u16).to_be_bytes());
+        data.extend_from_slice(page);
+        data
+    }
+
+    fn capabilities_page() -> Vec<u8> {
+        let mut page = vec![0u8; 32];
+        page[0] = 0x2A;
+        page[1] = 30;
+        page[5] = 0x01 | 0x02 | 0x10 | 0x20;
+        page[7] = 0x20;
+        page[8..10].copy_from_slice(&7056u16.to_be_bytes());
+        page[12..14].copy_from_slice(&2048u16.to_be_bytes());
+        page[14..16].copy_from_slice(&7056u16.to_be_bytes());
+        page
+    }
+
+    #[test]
+    fn builds_command_descriptor_blocks() {
+        assert_eq!(
+            build_mode_sense_capabilities_cdb(256),
+            [0x5A, 0x08, 0x2A, 0, 0, 0, 0, 0x01, 0x00, 0]
+        );
+        assert_eq!(
+            build_get_configuration_cdb(32),
+            [0x46, 0x02, 0x00, 0x1E, 0, 0, 0, 0x00, 0x20, 0]
+        );
+    }
+
+    #[test]
+    fn parses_capabilities_page() {
+        let capabilities =
+            parse_capabilities_page(&mode_sense_response(&capabilities_page())).unwrap();
+
+        assert_eq!(
+            capabilities,
+            DriveCapabilities {
+                cd_da_commands: true,
+                accurate_stream: true,
+                c2_pointers: true,
+                raw_subchannel: false,
+                corrected_subchannel: false,
+                isrc: true,
+                media_catalog_number: false,
+                cd_text: true,
+                lead_in: true,
+                max_read_speed: Some(7056),
+                current_read_speed: Some(7056),
+                max_write_speed: None,
+                buffer_size: Some(2048),
+            }
+        );
+    }
+
+    #[test]
+    fn skips_block_descriptors_and_rejects_other_pages() {
+        let mut data = mode_sense_response(&capabilities_page());
+        data[7] = 8;
+        data.splice(8..8, [0u8; 8]);
+        assert!(parse_capabilities_page(&data).unwrap().cd_da_commands);
+
+        let mut page = capabilities_page();
+        page[0] = 0x01;
+        assert!(parse_capabilities_page(&mode_sense_response(&page)).is_err());
+        assert!(parse_capabilities_page(&data[..12]).is_err());
+    }
+
+    #[test]
+    fn cd_read_feature_overrides_cd_text_and_adds_c2() {
+        let mut capabilities = DriveCapabilities {
+            cd_text: true,
+            ..DriveCapabilities::default()
+        };
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(&[0x00, 0x1E, 0x0B, 0x04, 0x02, 0, 0, 0]);
+
+        apply_cd_read_feature(&mut capabilities, &data);
+
+        assert!(!capabilities.cd_text);
+        assert!(capabilities.c2_pointers);
+
+        let mut unchanged = DriveCapabilities::default();
+        apply_cd_read_feature(&mut unchanged, &data[..8]);
+        assert_eq!(unchanged, DriveCapabilities::default());
+    }
+
+    #[test]
+    fn reader_reports_mock_capabilities_and_probes_the_lead_out() {
+        let drive = MockDrive::audio_cd(&[10]);
+        let capabilities = DriveCapabilities {
+            cd_da_commands: true,
+            ..DriveCapabilities::default()
+        };
+        drive.set_capabilities(capabilities.clone());
+        let reader = CdReader::from_backend(drive);
+        let toc = reader.read_toc().unwrap();
+
+        assert_eq!(reader.capabilities().unwrap(), capabilities);
+        assert!(!reader.can_read_lead_out(&toc).unwrap());
+    }
+}
@@ -0,0 +1,241 @@
+//! Raw SCSI traffic captures, used to reproduce drive-specific problems
+//! without access to the drive.
+//!
+//! A capture file starts with the 8-byte magic `CDDACAP1`, followed by one
+//! record per issued command:
+//!
+//! ```text
+//! u8       CDB length
+//! [u8]     CDB
+//! u8       outcome: 0 = data, 1 = check condition, 2 = transport error
+//! outcome 0: u32 LE length, transferred bytes
+//! outcome 1: u8 SCSI status, u8 sense length, sense bytes
+//! outcome 2: i32 LE OS error code
+//! ```
+
+mod replay;
+
+pub use replay::ReplayDrive;
+
+use std::io;
+
+const MAGIC: &[u8; 8] = b"CDDACAP1";
+
+const OUTCOME_DATA: u8 = 0;
+const OUTCOME_CHECK_CONDITION: u8 = 1;
+const OUTCOME_TRANSPORT_ERROR: u8 = 2;
+
+/// How the drive answered a single command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CommandOutcome {
+    /// The command completed; holds the bytes the drive transferred.
+    Data(Vec<u8>),
+    /// The drive reported a non-GOOD status, with the sense bytes it returned.
+    CheckCondition { status: u8, sense: Vec<u8> },
+    /// The OS transport failed before the command completed.
+    TransportError(i32),
+}
+
+/// One command descriptor block and the drive's answer to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CaptureRecord {
+    pub(crate) cdb: Vec<u8>,
+    pub(crate) outcome: CommandOutcome,
+}
+
+/// Appends records to a capture file as commands are issued.
+///
+/// Every record is flushed immediately, so the capture stays usable if the
+/// process crashes or hangs on a later command. Capturing is a diagnostic
+/// and must not affect the commands, so the first failed write stops it
+/// instead of failing the command. The file then ends with the last record
+/// written in full.
+#[cfg(any(target_os = "linux", test))]
+pub(crate) struct CaptureWriter {
+    file: std::sync::Mutex<Option<std::fs::File>>,
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl CaptureWriter {
+    pub(crate) fn create(path: &std::path::Path) -> io::Result<Self> {
+        use std::io::Write;
+
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(MAGIC)?;
+        Ok(Self {
+            file: std::sync::Mutex::new(Some(file)),
+        })
+    }
+
+    pub(crate) fn record(&self, record: &CaptureRecord) {
+        use std::io::{Seek, Write};
+
+        let mut bytes = Vec::new();
+        encode_record(record, &mut bytes);
+        let Ok(mut guard) = self.file.lock() else {
+            return;
+        };
+        let Some(file) = guard.as_mut() else {
+            return;
+        };
+        let Ok(complete) = file.stream_position() else {
+            *guard = None;
+            return;
+        };
+        if file.write_all(&bytes).and_then(|()| file.flush()).is_err() {
+            // drop a partly written record, so the file still decodes
+            let _ = file.set_len(complete);
+            *guard = None;
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn encode_record(record: &CaptureRecord, out: &mut Vec<u8>) {
+    out.push(record.cdb.len() as u8);
+    out.extend_from_slice(&record.cdb);
+    match &record.outcome {
+        CommandOutcome::Data(data) => {
+            out.push(OUTCOME_DATA);
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(data);
+        }
+        CommandOutcome::CheckCondition { status, sense } => {
+            let sense = &sense[..sense.len().min(u8::MAX as usize)];
+            out.push(OUTCOME_CHECK_CONDITION);
+            out.push(*status);
+            out.push(sense.len() as u8);
+            out.extend_from_slice(sense);
+        }
+        CommandOutcome::TransportError(code) => {
+            out.push(OUTCOME_TRANSPORT_ERROR);
+            out.extend_from_slice(&code.to_le_bytes());
+        }
+    }
+}
+
+/// Parse the contents of a capture file.
+pub(crate) fn decode_capture(data: &[u8]) -> io::Result<Vec<CaptureRecord>> {
+    let mut input = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| invalid_data("not a cd-da-reader capture file"))?;
+    let mut records = Vec::new();
+
+    while !input.is_empty() {
+        let cdb_len = take(&mut input, 1)?[0] as usize;
+        let cdb = take(&mut input, cdb_len)?.to_vec();
+        let outcome = match take(&mut input, 1)?[0] {
+            OUTCOME_DATA => {
+                let len = u32::from_le_bytes(take(&mut input, 4)?.try_into().unwrap());
+                CommandOutcome::Data(take(&mut input, len as usize)?.to_vec())
+            }
+            OUTCOME_CHECK_CONDITION => {
+                let status = take(&mut input, 1)?[0];
+                let sense_len = take(&mut input, 1)?[0] as usize;
+                CommandOutcome::CheckCondition {
+                    status,
+                    sense: take(&mut input, sense_len)?.to_vec(),
+                }
+            }
+            OUTCOME_TRANSPORT_ERROR => CommandOutcome::TransportError(i32::from_le_bytes(
+                take(&mut input, 4)?.try_into().unwrap(),
+            )),
+            other => return Err(invalid_data(&format!("unknown capture outcome {other}"))),
+        };
+        records.push(CaptureRecord { cdb, outcome });
+    }
+
+    Ok(records)
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(invalid_data("capture file is truncated"));
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<CaptureRecord> {
+        vec![
+            CaptureRecord {
+                cdb: vec![0x43, 0, 0, 0, 0, 0, 0, 0x08, 0, 0],
+                outcome: CommandOutcome::Data(vec![1, 2, 3, 4]),
+            },
+            CaptureRecord {
+                cdb: vec![0xBE; 12],
+                outcome: CommandOutcome::CheckCondition {
+                    status: 0x02,
+                    sense: vec![0x70, 0, 0x03, 0, 0, 0, 0, 10, 0, 0, 0, 0, 0x11, 0x05],
+                },
+            },
+            CaptureRecord {
+                cdb: vec![0x52; 10],
+                outcome: CommandOutcome::TransportError(5),
+            },
+        ]
+    }
+
+    #[test]
+    fn round_trips_records() {
+        let mut bytes = MAGIC.to_vec();
+        for record in &records() {
+            encode_record(record, &mut bytes);
+        }
+
+        assert_eq!(decode_capture(&bytes).unwrap(), records());
+    }
+
+    #[test]
+    fn writer_produces_a_decodable_file() {
+        let path = std::env::temp_dir().join(format!(
+            "cd-da-reader-capture-{}-writer.bin",
+            std::process::id()
+        ));
+        let writer = CaptureWriter::create(&path).unwrap();
+        for record in &records() {
+            writer.record(record);
+        }
+
+        let decoded = decode_capture(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(decoded, records());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn stops_capturing_after_a_failed_write() {
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open("/dev/full")
+            .unwrap();
+        let writer = CaptureWriter {
+            file: std::sync::Mutex::new(Some(file)),
+        };
+
+        for record in &records() {
+            writer.record(record);
+        }
+
+        assert!(writer.file.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_files() {
+        assert!(decode_capture(b"RIFF....").is_err());
+
+        let mut bytes = MAGIC.to_vec();
+        encode_record(&records()[0], &mut bytes);
+        bytes.pop();
+        assert!(decode_capture(&bytes).is_err());
+    }
+}
@@ -0,0 +1,340 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::{CaptureRecord, CommandOutcome, decode_capture};
+use crate::cache::build_read10_fua_cdb;
+use crate::capabilities::{
+    GET_CONFIGURATION_RESPONSE_SIZE, MODE_SENSE_RESPONSE_SIZE, apply_cd_read_feature,
+    build_get_configuration_cdb, build_mode_sense_capabilities_cdb, parse_capabilities_page,
+};
+use crate::data_reader::build_read_cd_cdb;
+use crate::data_reader::track_information::{
+    TRACK_INFORMATION_RESPONSE_SIZE, build_read_track_information_cdb, parse_track_information,
+};
+use crate::errors::parse_fixed_sense;
+use crate::inquiry::{INQUIRY_RESPONSE_SIZE, build_inquiry_cdb, parse_inquiry};
+use crate::media::{build_test_unit_ready_cdb, media_state_from_test_unit_ready};
+use crate::media_events::{
+    EVENT_STATUS_RESPONSE_SIZE, build_get_event_status_cdb, parse_media_event_status,
+};
+use crate::parse_toc::{TOC_BUFFER_SIZE, build_read_toc_cdb, parse_toc};
+use crate::speed::{build_set_cd_speed_cdb, build_set_streaming_cdb};
+use crate::tray::{build_prevent_allow_medium_removal_cdb, build_start_stop_unit_cdb};
+use crate::{
+    CdReaderError, DriveBackend, DriveCapabilities, InquiryData, MediaEventStatus, MediaState,
+    ReadSpeed, ScsiError, ScsiOp, SectorReadFormat, Toc, TrackInformation,
+};
+
+/// Backend that answers commands from a capture of real drive traffic.
+///
+/// Captures are recorded on Linux with `CdReader::open_path_with_capture`
+/// and can be replayed on any platform. Each command is answered with the
+/// first not yet used record that has an identical command descriptor block,
+/// so retries and failures happen exactly as they did on the original drive.
+///
+/// ```no_run
+/// use cd_da_reader::{CdReader, ReplayDrive};
+///
+/// let reader = CdReader::from_backend(ReplayDrive::open("capture.bin")?);
+/// let toc = reader.read_toc()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct ReplayDrive {
+    records: Mutex<Vec<Option<CaptureRecord>>>,
+}
+
+impl ReplayDrive {
+    /// Load a capture file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CdReaderError> {
+        let data = std::fs::read(path)?;
+        let records = decode_capture(&data)?;
+
+        Ok(Self {
+            records: Mutex::new(records.into_iter().map(Some).collect()),
+        })
+    }
+
+    fn execute(
+        &self,
+        cdb: &[u8],
+        op: ScsiOp,
+        lba: Option<u32>,
+        sectors: Option<u32>,
+    ) -> Result<Vec<u8>, CdReaderError> {
+        let record = {
+            let mut records = self.records.lock().map_err(|_| {
+                CdReaderError::Io(std::io::Error::other("replay state lock is poisoned"))
+            })?;
+            records
+                .iter_mut()
+                .find(|record| record.as_ref().is_some_and(|record| record.cdb == cdb))
+                .and_then(Option::take)
+                .ok_or_else(|| {
+                    CdReaderError::Io(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "capture has no remaining response for this command",
+                    ))
+                })?
+        };
+
+        match record.outcome {
+            CommandOutcome::Data(data) => Ok(data),
+            CommandOutcome::CheckCondition { status, sense } => {
+                let (sense_key, asc, ascq) = parse_fixed_sense(&sense, sense.len() as u8);
+                Err(CdReaderError::Scsi(ScsiError {
+                    op,
+                    lba,
+                    sectors,
+                    scsi_status: status,
+                    sense_key,
+                    asc,
+                    ascq,
+                }))
+            }
+            CommandOutcome::TransportError(code) => {
+                Err(CdReaderError::Io(std::io::Error::from_raw_os_error(code)))
+            }
+        }
+    }
+}
+
+impl DriveBackend for ReplayDrive {
+    fn read_toc(&self) -> Result<Toc, CdReaderError> {
+        let data = self.execute(
+            &build_read_toc_cdb(TOC_BUFFER_SIZE),
+            ScsiOp::ReadToc,
+            None,
+            None,
+        )?;
+
+        parse_toc(data).map_err(|error| CdReaderError::Parse(error.to_string()))
+    }
+
+    fn read_track_information(&self, track_number: u8) -> Result<TrackInformation, CdReaderError> {
+        let cdb =
+            build_read_track_information_cdb(track_number, TRACK_INFORMATION_RESPONSE_SIZE as u16);
+        let data = self.execute(&cdb, ScsiOp::ReadTrackInformation, None, None)?;
+
+        parse_track_information(&data).map_err(|error| CdReaderError::Parse(error.to_string()))
+    }
+
+    fn read_cd_chunk(
+        &self,
+        lba: u32,
+        sectors: u32,
+        format: SectorReadFormat,
+    ) -> Result<Vec<u8>, CdReaderError> {
+        self.execute(
+            &build_read_cd_cdb(lba, sectors, format),
+            ScsiOp::ReadCd,
+            Some(lba),
+            Some(sectors),
+        )
+    }
+
+    fn inquiry(&self) -> Result<InquiryData, CdReaderError> {
+        let cdb = build_inquiry_cdb(INQUIRY_RESPONSE_SIZE as u8);
+        let data = self.execute(&cdb, ScsiOp::Inquiry, None, None)?;
+
+        parse_inquiry(&data).map_err(|error| CdReaderError::Parse(error.to_string()))
+    }
+
+    fn capabilities(&self) -> Result<DriveCapabilities, CdReaderError> {
+        let cdb = build_mode_sense_capabilities_cdb(MODE_SENSE_RESPONSE_SIZE as u16);
+        let data = self.execute(&cdb, ScsiOp::ModeSense, None, None)?;
+        let mut capabilities = parse_capabilities_page(&data)
+            .map_err(|error| CdReaderError::Parse(error.to_string()))?;
+
+        let cdb = build_get_configuration_cdb(GET_CONFIGURATION_RESPONSE_SIZE as u16);
+        if let Ok(data) = self.execute(&cdb, ScsiOp::GetConfiguration, None, None) {
+            apply_cd_read_feature(&mut capabilities, &data);
+        }
+
+        Ok(capabilities)
+    }
+
+    fn eject(&self) -> Result<(), CdReaderError> {
+        let cdb = build_start_stop_unit_cdb(false);
+        self.execute(&cdb, ScsiOp::StartStopUnit, None, None)
+            .map(drop)
+    }
+
+    fn load(&self) -> Result<(), CdReaderError> {
+        let cdb = build_start_stop_unit_cdb(true);
+        self.execute(&cdb, ScsiOp::StartStopUnit, None, None)
+            .map(drop)
+    }
+
+    fn set_tray_locked(&self, locked: bool) -> Result<(), CdReaderError> {
+        let cdb = build_prevent_allow_medium_removal_cdb(locked);
+        self.execute(&cdb, ScsiOp::PreventAllowMediumRemoval, None, None)
+            .map(drop)
+    }
+
+    fn set_read_speed(&self, speed: ReadSpeed) -> Result<(), CdReaderError> {
+        // captures hold the command only, not the SET STREAMING descriptor
+        let cd_speed = self.execute(
+            &build_set_cd_speed_cdb(speed),
+            ScsiOp::SetCdSpeed,
+            None,
+            None,
+        );
+        let streaming = self.execute(&build_set_streaming_cdb(), ScsiOp::SetStreaming, None, None);
+
+        match cd_speed {
+            Ok(_) => Ok(()),
+            Err(error) => streaming.map(drop).map_err(|_| error),
+        }
+    }
+
+    fn media_state(&self) -> Result<MediaState, CdReaderError> {
+        let cdb = build_test_unit_ready_cdb();
+        let result = self.execute(&cdb, ScsiOp::TestUnitReady, None, None);
+
+        media_state_from_test_unit_ready(result.map(drop))
+    }
+
+    fn poll_media_event(&self) -> Result<MediaEventStatus, CdReaderError> {
+        let cdb = build_get_event_status_cdb(EVENT_STATUS_RESPONSE_SIZE as u16);
+        let data = self.execute(&cdb, ScsiOp::GetEventStatusNotification, None, None)?;
+
+        parse_media_event_status(&data).map_err(CdReaderError::Io)
+    }
+
+    fn invalidate_cache(&self, lba: u32) -> Result<(), CdReaderError> {
+        self.execute(
+            &build_read10_fua_cdb(lba),
+            ScsiOp::Read10,
+            Some(lba),
+            Some(1),
+        )
+        .map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::ReplayDrive;
+    use crate::capture::{CaptureRecord, CaptureWriter, CommandOutcome};
+    use crate::data_reader::build_read_cd_cdb;
+    use crate::parse_toc::{TOC_BUFFER_SIZE, build_read_toc_cdb};
+    use crate::{CdReader, CdReaderError, ReadOptions, RetryConfig, SectorReadFormat};
+
+    fn capture_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "cd-da-reader-replay-{}-{name}.bin",
+            std::process::id()
+        ))
+    }
+
+    fn write_capture(name: &str, records: &[CaptureRecord]) -> PathBuf {
+        let path = capture_path(name);
+        let writer = CaptureWriter::create(&path).unwrap();
+        for record in records {
+            writer.record(record);
+        }
+        path
+    }
+
+    /// Single audio track from LBA 0 to a lead-out at LBA 2.
+    fn toc_response() -> Vec<u8> {
+        vec![
+            0x00, 0x12, 0x01, 0x01, //
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x00, 0x02,
+        ]
+    }
+
+    fn medium_error_sense() -> Vec<u8> {
+        let mut sense = vec![0u8; 18];
+        sense[0] = 0x70;
+        sense[2] = 0x03;
+        sense[12] = 0x11;
+        sense[13] = 0x05;
+        sense
+    }
+
+    #[test]
+    fn replays_responses_and_failures_in_order() {
+        let read_cd = build_read_cd_cdb(0, 2, SectorReadFormat::Audio).to_vec();
+        let path = write_capture(
+            "ordered",
+            &[
+                CaptureRecord {
+                    cdb: build_read_toc_cdb(TOC_BUFFER_SIZE).to_vec(),
+                    outcome: CommandOutcome::Data(toc_response()),
+                },
+                CaptureRecord {
+                    cdb: read_cd.clone(),
+                    outcome: CommandOutcome::CheckCondition {
+                        status: 0x02,
+                        sense: medium_error_sense(),
+                    },
+                },
+                CaptureRecord {
+                    cdb: read_cd,
+                    outcome: CommandOutcome::Data(vec![7; 2 * 2352]),
+                },
+            ],
+        );
+        let reader = CdReader::from_backend(ReplayDrive::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let toc = reader.read_toc().unwrap();
+        assert_eq!(toc.leadout_lba, 2);
+
+        let no_retry =
+            ReadOptions::default().with_retry(RetryConfig::default().with_max_attempts(1));
+        match reader.read_track_with_options(&toc, 1, &no_retry) {
+            Err(CdReaderError::Scsi(error)) => {
+                assert_eq!(error.lba, Some(0));
+                assert_eq!(error.sectors, Some(2));
+                assert_eq!(error.scsi_status, 0x02);
+                assert_eq!(
+                    (error.sense_key, error.asc, error.ascq),
+                    (Some(3), Some(0x11), Some(5))
+                );
+            }
+            other => panic!("expected a replayed SCSI error, got {other:?}"),
+        }
+
+        assert_eq!(reader.read_track(&toc, 1).unwrap(), vec![7; 2 * 2352]);
+    }
+
+    #[test]
+    fn reports_commands_missing_from_the_capture() {
+        let path = write_capture("missing", &[]);
+        let reader = CdReader::from_backend(ReplayDrive::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        match reader.read_toc() {
+            Err(CdReaderError::Io(error)) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::NotFound)
+            }
+            other => panic!("expected a missing response, got {other:?}"),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn records_and_replays_transport_errors_on_linux() {
+        // SG_IO on a non-SCSI device fails in the ioctl itself.
+        let path = capture_path("linux");
+        let recording = CdReader::open_path_with_capture("/dev/null", &path).unwrap();
+        let recorded = match recording.read_toc() {
+            Err(CdReaderError::Io(error)) => error.raw_os_error(),
+            other => panic!("expected an ioctl failure, got {other:?}"),
+        };
+        drop(recording);
+
+        let replay = CdReader::from_backend(ReplayDrive::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        match replay.read_toc() {
+            Err(CdReaderError::Io(error)) => assert_eq!(error.raw_os_error(), recorded),
+            other => panic!("expected the recorded failure, got {other:?}"),
+        }
+    }
+}
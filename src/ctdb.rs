@@ -0,0 +1,378 @@
+//! [CUETools Database](http://db.cuetools.net/) (CTDB) TOC IDs, whole-disc
+//! CRC32 and lookup response parsing.
+//!
+//! CTDB identifies a disc by its audio track layout and verifies it with a
+//! single CRC32 over all audio tracks, which makes it a useful second opinion
+//! next to the per-track AccurateRip checksums.
+
+use crate::disc_id::{musicbrainz_base64, sha1};
+use crate::{CdReaderError, Toc, utils};
+
+const SAMPLES_PER_SECTOR: u32 = 588;
+const BYTES_PER_SAMPLE: u64 = 4;
+/// CTDB leaves out the first ten sectors of the disc, and the last ten
+/// sectors plus whatever does not fill a whole ten-sector stride.
+const CRC_STRIDE_SAMPLES: u32 = 10 * SAMPLES_PER_SECTOR;
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+impl Toc {
+    /// Compute the CTDB TOC ID of the audio session.
+    ///
+    /// Track offsets are relative to the first audio track, so discs that
+    /// only differ in the position of the first track share an ID. On CD-Extra
+    /// discs the data session is left out, like in
+    /// [`Toc::musicbrainz_disc_id`].
+    pub fn ctdb_toc_id(&self) -> Result<String, CdReaderError> {
+        let (tracks, leadout_lba) = utils::first_session_tracks(self)?;
+        let audio: Vec<u32> = tracks
+            .iter()
+            .filter(|track| track.is_audio)
+            .map(|track| track.start_lba)
+            .collect();
+        let Some(&first_start) = audio.first() else {
+            return Err(CdReaderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "TOC does not contain any audio tracks",
+            )));
+        };
+
+        let mut input = String::with_capacity(800);
+        for start in &audio[1..] {
+            input.push_str(&format!("{:08X}", start - first_start));
+        }
+        input.push_str(&format!("{:08X}", leadout_lba.saturating_sub(first_start)));
+        while input.len() < 800 {
+            input.push('0');
+        }
+
+        Ok(musicbrainz_base64(&sha1(input.as_bytes())))
+    }
+}
+
+/// Incremental CTDB CRC32 over the audio of a whole disc.
+///
+/// Feed every audio track in disc order, exactly as returned by
+/// [`CdReader::read_track`](crate::CdReader::read_track), then call
+/// [`finish`](Self::finish).
+#[derive(Debug, Clone)]
+pub struct CtdbHasher {
+    /// Byte position of the next `update` within the disc's audio.
+    position: u64,
+    check_from: u64,
+    check_to: u64,
+    crc: u32,
+}
+
+impl CtdbHasher {
+    /// Create a hasher for `total_samples` stereo samples of disc audio.
+    pub fn new(total_samples: u32) -> Self {
+        let skipped_tail = CRC_STRIDE_SAMPLES + total_samples % CRC_STRIDE_SAMPLES;
+        let check_to = total_samples.saturating_sub(skipped_tail);
+
+        Self {
+            position: 0,
+            check_from: u64::from(CRC_STRIDE_SAMPLES) * BYTES_PER_SAMPLE,
+            check_to: u64::from(check_to) * BYTES_PER_SAMPLE,
+            crc: 0xFFFF_FFFF,
+        }
+    }
+
+    /// Create a hasher for the audio tracks of a TOC, using the same track
+    /// boundaries as [`CdReader::read_track`](crate::CdReader::read_track).
+    pub fn for_toc(toc: &Toc) -> Result<Self, CdReaderError> {
+        let (tracks, _) = utils::first_session_tracks(toc)?;
+        let mut sectors = 0;
+        for track in tracks.iter().filter(|track| track.is_audio) {
+            sectors += utils::get_track_bounds(toc, track.number)?.1;
+        }
+
+        Ok(Self::new(sectors * SAMPLES_PER_SECTOR))
+    }
+
+    /// Add the next part of the disc's PCM data.
+    pub fn update(&mut self, pcm: &[u8]) {
+        let start = self.position;
+        let end = start + pcm.len() as u64;
+        self.position = end;
+
+        let from = start.max(self.check_from);
+        let to = end.min(self.check_to);
+        if from >= to {
+            return;
+        }
+
+        for byte in &pcm[(from - start) as usize..(to - start) as usize] {
+            self.crc =
+                CRC32_TABLE[((self.crc ^ u32::from(*byte)) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    /// Return the CRC32 of all data passed to [`update`](Self::update).
+    pub fn finish(self) -> u32 {
+        !self.crc
+    }
+}
+
+/// One submission from a CTDB lookup response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CtdbEntry {
+    /// Database entry ID.
+    pub id: u32,
+    /// Number of submissions that agree with this entry.
+    pub confidence: u32,
+    /// Whole-disc CRC32.
+    pub crc32: u32,
+    /// Per-track CRC32 values, when the response includes them.
+    pub track_crcs: Vec<u32>,
+    /// TOC of the submitted disc, in the `start:start:...:leadout` form.
+    pub toc: String,
+}
+
+/// Result of checking a disc CRC against a CTDB lookup response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtdbVerdict {
+    /// An entry has the same CRC.
+    Match {
+        /// Confidence of the matching entry.
+        confidence: u32,
+        /// Sum of the confidence of all entries for this TOC.
+        total_confidence: u32,
+    },
+    /// The disc is known, but no entry has the same CRC.
+    Mismatch {
+        /// Sum of the confidence of all entries for this TOC.
+        total_confidence: u32,
+    },
+    /// The response contains no entries.
+    NotInDatabase,
+}
+
+/// Parsed XML response of a CTDB lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CtdbResponse {
+    /// Entries in the order they appear in the response.
+    pub entries: Vec<CtdbEntry>,
+}
+
+impl CtdbResponse {
+    /// Parse a CTDB lookup response (`lookup2.php` XML).
+    ///
+    /// Only the `<entry>` elements are read; everything else, including
+    /// MusicBrainz metadata, is ignored.
+    pub fn parse(xml: &str) -> Result<Self, CdReaderError> {
+        let mut entries = Vec::new();
+
+        for (start, _) in xml.match_indices("<entry") {
+            let rest = &xml[start + "<entry".len()..];
+            if !rest.starts_with(|ch: char| ch.is_whitespace() || ch == '/' || ch == '>') {
+                continue;
+            }
+            let end = rest
+                .find('>')
+                .ok_or_else(|| parse_error("unterminated <entry> element"))?;
+            let attributes = parse_attributes(&rest[..end])?;
+            let attribute = |name: &str| {
+                attributes
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.as_str())
+            };
+
+            let track_crcs = attribute("trackcrcs")
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(parse_hex)
+                .collect::<Result<_, _>>()?;
+            entries.push(CtdbEntry {
+                id: parse_decimal(attribute("id").unwrap_or("0"))?,
+                confidence: parse_decimal(
+                    attribute("confidence")
+                        .ok_or_else(|| parse_error("entry without confidence"))?,
+                )?,
+                crc32: parse_hex(
+                    attribute("crc32").ok_or_else(|| parse_error("entry without crc32"))?,
+                )?,
+                track_crcs,
+                toc: attribute("toc").unwrap_or_default().to_string(),
+            });
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Compare a whole-disc CRC from [`CtdbHasher`] with the response.
+    pub fn verify(&self, crc32: u32) -> CtdbVerdict {
+        if self.entries.is_empty() {
+            return CtdbVerdict::NotInDatabase;
+        }
+
+        let total_confidence = self.entries.iter().map(|entry| entry.confidence).sum();
+        match self.entries.iter().find(|entry| entry.crc32 == crc32) {
+            Some(entry) => CtdbVerdict::Match {
+                confidence: entry.confidence,
+                total_confidence,
+            },
+            None => CtdbVerdict::Mismatch { total_confidence },
+        }
+    }
+}
+
+/// Parse `name="value"` pairs from the inside of an XML start tag.
+fn parse_attributes(tag: &str) -> Result<Vec<(String, String)>, CdReaderError> {
+    let mut attributes = Vec::new();
+    let mut rest = tag.trim_end_matches('/').trim();
+
+    while !rest.is_empty() {
+        let (name, value_part) = rest
+            .split_once('=')
+            .ok_or_else(|| parse_error("malformed attribute"))?;
+        let value_part = value_part.trim_start();
+        let quote = value_part
+            .chars()
+            .next()
+            .filter(|ch| *ch == '"' || *ch == '\'')
+            .ok_or_else(|| parse_error("unquoted attribute value"))?;
+        let value_end = value_part[1..]
+            .find(quote)
+            .ok_or_else(|| parse_error("unterminated attribute value"))?;
+
+        attributes.push((
+            name.trim().to_string(),
+            unescape(&value_part[1..1 + value_end]),
+        ));
+        rest = value_part[value_end + 2..].trim_start();
+    }
+
+    Ok(attributes)
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn parse_hex(value: &str) -> Result<u32, CdReaderError> {
+    u32::from_str_radix(value, 16).map_err(|_| parse_error(&format!("invalid CRC {value}")))
+}
+
+fn parse_decimal(value: &str) -> Result<u32, CdReaderError> {
+    value
+        .parse()
+        .map_err(|_| parse_error(&format!("invalid number {value}")))
+}
+
+fn parse_error(message: &str) -> CdReaderError {
+    CdReaderError::Parse(format!("CTDB response: {message}"))
+}
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{pcm, sample_toc, toc};
+
+    #[test]
+    fn computes_toc_ids() {
+        assert_eq!(
+            sample_toc().ctdb_toc_id().unwrap(),
+            "ugTPQAwa66e8AuugJsX57uKy1Ik-"
+        );
+
+        // Relative to the first track, with the CD-Extra data session excluded.
+        let cd_extra = toc(
+            &[
+                (1, 32, true),
+                (2, 10_000, true),
+                (3, 20_000, true),
+                (4, 41_400, false),
+            ],
+            60_000,
+        );
+        assert_eq!(
+            cd_extra.ctdb_toc_id().unwrap(),
+            "Vmk7XBge0ELOKcBsKz_5f2ufsy0-"
+        );
+    }
+
+    #[test]
+    fn computes_disc_crc_in_chunks() {
+        let pcm = pcm(45);
+        let toc = toc(&[(1, 0, true), (2, 20, true)], 45);
+
+        let mut hasher = CtdbHasher::for_toc(&toc).unwrap();
+        for chunk in pcm.chunks(7_001) {
+            hasher.update(chunk);
+        }
+
+        assert_eq!(hasher.finish(), 0x1bf49d0d);
+        assert_eq!(CtdbHasher::new(0).finish(), 0);
+    }
+
+    #[test]
+    fn parses_lookup_responses_and_verifies_crcs() {
+        let response = CtdbResponse::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ctdb xmlns="http://db.cuetools.net/ns/mmd-1.0#">
+              <entry confidence="41" crc32="1bf49d0d" id="123" npar="8" stride="5880"
+                     toc="0:20:45" trackcrcs="0a0b0c0d 01020304" />
+              <entry confidence="2" crc32="deadbeef" id="124" toc="0:20:45"/>
+              <musicbrainz><metadata artist="A &amp; B"/></musicbrainz>
+            </ctdb>"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.entries.len(), 2);
+        assert_eq!(
+            response.entries[0],
+            CtdbEntry {
+                id: 123,
+                confidence: 41,
+                crc32: 0x1bf49d0d,
+                track_crcs: vec![0x0a0b0c0d, 0x01020304],
+                toc: "0:20:45".to_string(),
+            }
+        );
+        assert_eq!(
+            response.verify(0x1bf49d0d),
+            CtdbVerdict::Match {
+                confidence: 41,
+                total_confidence: 43,
+            }
+        );
+        assert_eq!(
+            response.verify(1),
+            CtdbVerdict::Mismatch {
+                total_confidence: 43
+            }
+        );
+        assert_eq!(
+            CtdbResponse::parse("<ctdb/>").unwrap().verify(1),
+            CtdbVerdict::NotInDatabase
+        );
+        assert!(CtdbResponse::parse(r#"<entry confidence="x" crc32="1"/>"#).is_err());
+    }
+}
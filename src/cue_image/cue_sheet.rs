@@ -0,0 +1,328 @@
+//! Minimal CUE sheet parser covering the commands that affect the disc layout.
+//!
+//! Metadata commands (`TITLE`, `PERFORMER`, `CATALOG`, `FLAGS`, `ISRC`, ...)
+//! are accepted and ignored. `REM SESSION nn` is understood because multi-session
+//! images (CD-Extra) use it to mark the start of the data session.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CueTrackMode {
+    Audio,
+    Mode1Cooked,
+    Mode1Raw,
+    Mode2Raw,
+}
+
+impl CueTrackMode {
+    /// Bytes stored per sector in the image file.
+    pub(super) fn sector_size(self) -> u64 {
+        match self {
+            Self::Audio | Self::Mode1Raw | Self::Mode2Raw => 2352,
+            Self::Mode1Cooked => 2048,
+        }
+    }
+
+    pub(super) fn is_audio(self) -> bool {
+        matches!(self, Self::Audio)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CueTrack {
+    pub(super) number: u8,
+    pub(super) mode: CueTrackMode,
+    pub(super) session: u8,
+    /// `PREGAP` length in sectors; these sectors are not stored in the file.
+    pub(super) pregap: u32,
+    /// `POSTGAP` length in sectors; these sectors are not stored in the file.
+    pub(super) postgap: u32,
+    /// `(index number, position in sectors from the start of the file)`.
+    pub(super) indexes: Vec<(u8, u32)>,
+}
+
+impl CueTrack {
+    pub(super) fn index(&self, number: u8) -> Option<u32> {
+        self.indexes
+            .iter()
+            .find(|(index, _)| *index == number)
+            .map(|(_, position)| *position)
+    }
+
+    /// Position of the earliest index, which is where the track's data starts in the file.
+    pub(super) fn first_index(&self) -> u32 {
+        self.indexes
+            .iter()
+            .map(|(_, position)| *position)
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CueFile {
+    pub(super) name: String,
+    pub(super) tracks: Vec<CueTrack>,
+}
+
+pub(super) fn parse_cue_sheet(text: &str) -> Result<Vec<CueFile>, String> {
+    let mut files: Vec<CueFile> = Vec::new();
+    let mut session = 1u8;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let tokens = tokenize(line);
+        let Some(command) = tokens.first() else {
+            continue;
+        };
+
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => {
+                let (name, file_type) = match tokens.as_slice() {
+                    [_, name, file_type] => (name, file_type),
+                    _ => return Err(format!("line {line_no}: FILE expects a name and a type")),
+                };
+                if !file_type.eq_ignore_ascii_case("BINARY") {
+                    return Err(format!(
+                        "line {line_no}: unsupported FILE type {file_type}, only BINARY is supported"
+                    ));
+                }
+                files.push(CueFile {
+                    name: name.clone(),
+                    tracks: Vec::new(),
+                });
+            }
+            "TRACK" => {
+                let file = files
+                    .last_mut()
+                    .ok_or_else(|| format!("line {line_no}: TRACK before FILE"))?;
+                let (number, mode) = match tokens.as_slice() {
+                    [_, number, mode] => (number, mode),
+                    _ => return Err(format!("line {line_no}: TRACK expects a number and a mode")),
+                };
+                let number = number
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|number| (1..=99).contains(number))
+                    .ok_or_else(|| format!("line {line_no}: invalid track number {number}"))?;
+                file.tracks.push(CueTrack {
+                    number,
+                    mode: parse_track_mode(mode)
+                        .ok_or_else(|| format!("line {line_no}: unsupported track mode {mode}"))?,
+                    session,
+                    pregap: 0,
+                    postgap: 0,
+                    indexes: Vec::new(),
+                });
+            }
+            "INDEX" => {
+                let track = current_track(&mut files, line_no, "INDEX")?;
+                let (number, position) = match tokens.as_slice() {
+                    [_, number, position] => (number, position),
+                    _ => {
+                        return Err(format!(
+                            "line {line_no}: INDEX expects a number and a position"
+                        ));
+                    }
+                };
+                let number = number
+                    .parse::<u8>()
+                    .map_err(|_| format!("line {line_no}: invalid index number {number}"))?;
+                let position = parse_msf(position)
+                    .ok_or_else(|| format!("line {line_no}: invalid index position {position}"))?;
+                track.indexes.push((number, position));
+            }
+            "PREGAP" | "POSTGAP" => {
+                let track = current_track(&mut files, line_no, command)?;
+                let length = tokens
+                    .get(1)
+                    .and_then(|length| parse_msf(length))
+                    .ok_or_else(|| format!("line {line_no}: invalid {command} length"))?;
+                if command.eq_ignore_ascii_case("PREGAP") {
+                    track.pregap = length;
+                } else {
+                    track.postgap = length;
+                }
+            }
+            "REM" => {
+                if let [_, key, value, ..] = tokens.as_slice()
+                    && key.eq_ignore_ascii_case("SESSION")
+                {
+                    session = value
+                        .parse::<u8>()
+                        .map_err(|_| format!("line {line_no}: invalid session number {value}"))?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if files.iter().all(|file| file.tracks.is_empty()) {
+        return Err("cue sheet does not contain any tracks".to_string());
+    }
+
+    for track in files.iter().flat_map(|file| file.tracks.iter()) {
+        if track.index(1).is_none() {
+            return Err(format!("track {} has no INDEX 01", track.number));
+        }
+    }
+
+    Ok(files)
+}
+
+fn current_track<'a>(
+    files: &'a mut [CueFile],
+    line_no: usize,
+    command: &str,
+) -> Result<&'a mut CueTrack, String> {
+    files
+        .last_mut()
+        .and_then(|file| file.tracks.last_mut())
+        .ok_or_else(|| format!("line {line_no}: {command} before TRACK"))
+}
+
+fn parse_track_mode(mode: &str) -> Option<CueTrackMode> {
+    match mode.to_ascii_uppercase().as_str() {
+        "AUDIO" => Some(CueTrackMode::Audio),
+        "MODE1/2048" => Some(CueTrackMode::Mode1Cooked),
+        "MODE1/2352" => Some(CueTrackMode::Mode1Raw),
+        "MODE2/2352" => Some(CueTrackMode::Mode2Raw),
+        _ => None,
+    }
+}
+
+/// Parse an `mm:ss:ff` position into a sector count.
+fn parse_msf(value: &str) -> Option<u32> {
+    let mut parts = value.split(':').map(|part| part.parse::<u32>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || seconds >= 60 || frames >= 75 {
+        return None;
+    }
+
+    minutes
+        .checked_mul(60)?
+        .checked_add(seconds)?
+        .checked_mul(75)?
+        .checked_add(frames)
+}
+
+/// Split a cue line into whitespace-separated tokens, keeping quoted strings intact.
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = String::new();
+        if ch == '"' {
+            chars.next();
+            for ch in chars.by_ref() {
+                if ch == '"' {
+                    break;
+                }
+                token.push(ch);
+            }
+        } else {
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                token.push(ch);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_files_tracks_and_indexes() {
+        let files = parse_cue_sheet(
+            r#"
+            REM GENRE Rock
+            PERFORMER "Some Band"
+            FILE "Disc Image.bin" BINARY
+              TRACK 01 MODE1/2352
+                INDEX 01 00:00:00
+              TRACK 02 AUDIO
+                TITLE "Second"
+                PREGAP 00:02:00
+                INDEX 01 01:00:00
+            REM SESSION 02
+            FILE "data.bin" BINARY
+              TRACK 03 MODE2/2352
+                INDEX 00 00:00:00
+                INDEX 01 00:02:00
+                POSTGAP 00:00:10
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].name, "Disc Image.bin");
+        assert_eq!(
+            files[0].tracks[1],
+            CueTrack {
+                number: 2,
+                mode: CueTrackMode::Audio,
+                session: 1,
+                pregap: 150,
+                postgap: 0,
+                indexes: vec![(1, 4500)],
+            }
+        );
+        assert_eq!(
+            files[1].tracks[0],
+            CueTrack {
+                number: 3,
+                mode: CueTrackMode::Mode2Raw,
+                session: 2,
+                pregap: 0,
+                postgap: 10,
+                indexes: vec![(0, 0), (1, 150)],
+            }
+        );
+        assert_eq!(files[1].tracks[0].first_index(), 0);
+    }
+
+    #[test]
+    fn rejects_tracks_outside_files_and_missing_index_01() {
+        assert!(parse_cue_sheet("TRACK 01 AUDIO\nINDEX 01 00:00:00").is_err());
+        assert!(
+            parse_cue_sheet("FILE \"a.bin\" BINARY\nTRACK 01 AUDIO\nINDEX 00 00:00:00").is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_file_types_and_modes() {
+        assert!(parse_cue_sheet("FILE \"a.wav\" WAVE\nTRACK 01 AUDIO\nINDEX 01 00:00:00").is_err());
+        assert!(
+            parse_cue_sheet("FILE \"a.bin\" BINARY\nTRACK 01 MODE2/2336\nINDEX 01 00:00:00")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_msf_positions() {
+        assert_eq!(parse_msf("00:00:00"), Some(0));
+        assert_eq!(parse_msf("01:02:03"), Some((60 + 2) * 75 + 3));
+        assert_eq!(parse_msf("00:60:00"), None);
+        assert_eq!(parse_msf("00:00:75"), None);
+        assert_eq!(parse_msf("00:00"), None);
+        assert_eq!(parse_msf("954437:00:00"), Some(954_437 * 60 * 75));
+        assert_eq!(parse_msf("954438:00:00"), None);
+        assert_eq!(parse_msf("99999999:00:00"), None);
+        assert!(
+            parse_cue_sheet("FILE \"a.bin\" BINARY\nTRACK 01 AUDIO\nINDEX 01 99999999:00:00")
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,527 @@
+mod cue_sheet;
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+
+use cue_sheet::{CueFile, CueTrackMode, parse_cue_sheet};
+
+use crate::c2::C2_POINTERS_SIZE;
+use crate::parse_toc::lba_to_msf;
+use crate::{
+    CdReaderError, DriveBackend, ScsiError, ScsiOp, SectorReadFormat, Toc, Track, TrackInformation,
+};
+
+/// Sectors between the end of the audio session and the first data track of
+/// the next session on a multi-session disc (lead-out, lead-in and pregap).
+const SESSION_GAP_SECTORS: u32 = 11_400;
+
+// Sync (12) + header (4) precede the user data of a raw Mode 1 sector.
+const MODE1_USER_DATA_OFFSET: usize = 16;
+
+/// Virtual drive backed by a BIN/CUE disc image.
+///
+/// The image is exposed through [`DriveBackend`], so it can be wrapped with
+/// [`CdReader::from_backend`](crate::CdReader::from_backend) and read with the
+/// same API as a physical drive:
+///
+/// ```no_run
+/// use cd_da_reader::{CdReader, CueImage};
+///
+/// let reader = CdReader::from_backend(CueImage::open("album.cue")?);
+/// let toc = reader.read_toc()?;
+/// let data = reader.read_track(&toc, 1)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// Track positions are laid out the same way a drive reports them: `PREGAP`
+/// and `POSTGAP` sectors are inserted as silence, and a `REM SESSION` change
+/// (used by CD-Extra images) adds the 11,400-sector gap between sessions, so
+/// the resulting [`Toc`] matches the one read from the physical disc.
+///
+/// Only `BINARY` files with `AUDIO`, `MODE1/2048`, `MODE1/2352` and
+/// `MODE2/2352` tracks are supported. Raw sector formats cannot be served from
+/// `MODE1/2048` tracks, since the image does not store sync, header and ECC data.
+pub struct CueImage {
+    toc: Toc,
+    tracks: Vec<ImageTrack>,
+    extents: Vec<Extent>,
+    files: Vec<Mutex<File>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImageTrack {
+    number: u8,
+    mode: CueTrackMode,
+    session: u8,
+    start_lba: u32,
+    end_lba: u32,
+}
+
+/// Contiguous LBA range stored in a single file, or generated as silence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Extent {
+    start_lba: u32,
+    sectors: u32,
+    mode: CueTrackMode,
+    /// `(file index, byte offset of start_lba)`; `None` for pregap/postgap silence.
+    source: Option<(usize, u64)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Layout {
+    tracks: Vec<ImageTrack>,
+    extents: Vec<Extent>,
+    leadout_lba: u32,
+}
+
+impl CueImage {
+    /// Open a cue sheet and the `.bin` files it references.
+    ///
+    /// File names are resolved relative to the directory of the cue sheet.
+    pub fn open(cue_path: impl AsRef<Path>) -> Result<Self, CdReaderError> {
+        let cue_path = cue_path.as_ref();
+        let text = std::fs::read_to_string(cue_path)?;
+        let cue_files = parse_cue_sheet(&text).map_err(CdReaderError::Parse)?;
+        let directory = cue_path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut files = Vec::with_capacity(cue_files.len());
+        let mut file_sizes = Vec::with_capacity(cue_files.len());
+        for cue_file in &cue_files {
+            let file = File::open(directory.join(&cue_file.name))?;
+            file_sizes.push(file.metadata()?.len());
+            files.push(Mutex::new(file));
+        }
+
+        let layout = build_layout(&cue_files, &file_sizes).map_err(CdReaderError::Parse)?;
+        let toc = Toc {
+            first_track: layout.tracks.iter().map(|t| t.number).min().unwrap_or(1),
+            last_track: layout.tracks.iter().map(|t| t.number).max().unwrap_or(1),
+            tracks: layout
+                .tracks
+                .iter()
+                .map(|track| Track {
+                    number: track.number,
+                    start_lba: track.start_lba,
+                    start_msf: lba_to_msf(track.start_lba),
+                    is_audio: track.mode.is_audio(),
+                })
+                .collect(),
+            leadout_lba: layout.leadout_lba,
+        };
+
+        Ok(Self {
+            toc,
+            tracks: layout.tracks,
+            extents: layout.extents,
+            files,
+        })
+    }
+
+    fn find_extent(&self, lba: u32) -> Option<&Extent> {
+        let idx = self
+            .extents
+            .partition_point(|extent| extent.start_lba + extent.sectors <= lba);
+        self.extents
+            .get(idx)
+            .filter(|extent| extent.start_lba <= lba)
+    }
+
+    fn read_stored(&self, extent: &Extent, lba: u32, sectors: u32) -> std::io::Result<Vec<u8>> {
+        let size = extent.mode.sector_size();
+        let mut stored = vec![0u8; (sectors as u64 * size) as usize];
+        if let Some((file_idx, byte_offset)) = extent.source {
+            let offset = byte_offset + u64::from(lba - extent.start_lba) * size;
+            let mut file = self.files[file_idx]
+                .lock()
+                .map_err(|_| std::io::Error::other("image file lock is poisoned"))?;
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut stored)?;
+        }
+
+        Ok(stored)
+    }
+}
+
+impl DriveBackend for CueImage {
+    fn read_toc(&self) -> Result<Toc, CdReaderError> {
+        Ok(self.toc.clone())
+    }
+
+    fn read_track_information(&self, track_number: u8) -> Result<TrackInformation, CdReaderError> {
+        let track = self
+            .tracks
+            .iter()
+            .find(|track| track.number == track_number)
+            .ok_or_else(|| illegal_request(ScsiOp::ReadTrackInformation, None, None, 0x24))?;
+
+        Ok(TrackInformation {
+            track_number: track.number.into(),
+            session_number: track.session.into(),
+            track_mode: if track.mode.is_audio() { 0x00 } else { 0x04 },
+            data_mode: match track.mode {
+                CueTrackMode::Audio => 0x0F,
+                CueTrackMode::Mode1Cooked | CueTrackMode::Mode1Raw => 0x01,
+                CueTrackMode::Mode2Raw => 0x02,
+            },
+            start_lba: track.start_lba,
+            track_size: track.end_lba - track.start_lba,
+        })
+    }
+
+    fn read_cd_chunk(
+        &self,
+        lba: u32,
+        sectors: u32,
+        format: SectorReadFormat,
+    ) -> Result<Vec<u8>, CdReaderError> {
+        let mut out = Vec::with_capacity(sectors as usize * format.sector_size());
+        let mut current = lba;
+        let end = lba
+            .checked_add(sectors)
+            .ok_or_else(|| lba_out_of_range(lba, sectors))?;
+
+        while current < end {
+            let extent = self
+                .find_extent(current)
+                .ok_or_else(|| lba_out_of_range(lba, sectors))?;
+            let run = (extent.start_lba + extent.sectors).min(end) - current;
+            let stored = self.read_stored(extent, current, run)?;
+
+            for sector in stored.chunks_exact(extent.mode.sector_size() as usize) {
+                let converted = convert_sector(extent.mode, sector, format).ok_or_else(|| {
+                    if extent.mode == CueTrackMode::Mode1Cooked && !format.is_audio() {
+                        CdReaderError::Io(std::io::Error::new(
+                            std::io::ErrorKind::Unsupported,
+                            "raw sectors are not stored in MODE1/2048 image tracks",
+                        ))
+                    } else {
+                        illegal_request(ScsiOp::ReadCd, Some(lba), Some(sectors), 0x64)
+                    }
+                })?;
+                out.extend_from_slice(converted);
+                if format == SectorReadFormat::AudioWithC2 {
+                    // images hold what was read, so nothing is flagged
+                    out.resize(out.len() + C2_POINTERS_SIZE, 0);
+                }
+            }
+            current += run;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Return the part of a stored sector requested by `format`, or `None` when a
+/// drive would reject the read for this track type.
+fn convert_sector(mode: CueTrackMode, stored: &[u8], format: SectorReadFormat) -> Option<&[u8]> {
+    match (mode, format) {
+        (CueTrackMode::Audio, SectorReadFormat::Audio | SectorReadFormat::AudioWithC2) => {
+            Some(stored)
+        }
+        (CueTrackMode::Mode1Cooked, SectorReadFormat::Mode1Cooked) => Some(stored),
+        (CueTrackMode::Mode1Raw, SectorReadFormat::Mode1Cooked) => {
+            Some(&stored[MODE1_USER_DATA_OFFSET..MODE1_USER_DATA_OFFSET + 2048])
+        }
+        (CueTrackMode::Mode1Raw, SectorReadFormat::Mode1Raw | SectorReadFormat::Mode2Raw)
+        | (CueTrackMode::Mode2Raw, SectorReadFormat::Mode2Raw) => Some(stored),
+        _ => None,
+    }
+}
+
+/// Place every track of the cue sheet on a continuous LBA axis.
+fn build_layout(files: &[CueFile], file_sizes: &[u64]) -> Result<Layout, String> {
+    let mut tracks: Vec<ImageTrack> = Vec::new();
+    let mut extents = Vec::new();
+    let mut cursor = 0u64;
+    let mut session = 1u8;
+
+    for (file_idx, (file, &file_size)) in files.iter().zip(file_sizes).enumerate() {
+        let mut byte_start = 0u64;
+
+        for (track_idx, track) in file.tracks.iter().enumerate() {
+            let size = track.mode.sector_size();
+            // the first track of a file also owns any sectors before its first index
+            let first_frame = if track_idx == 0 {
+                0
+            } else {
+                track.first_index()
+            };
+            let index01 = track.index(1).unwrap_or(first_frame);
+            if index01 < first_frame {
+                return Err(format!(
+                    "track {} has INDEX 01 before its data",
+                    track.number
+                ));
+            }
+
+            let stored_sectors = match file.tracks.get(track_idx + 1) {
+                Some(next) => next
+                    .first_index()
+                    .checked_sub(first_frame)
+                    .map(u64::from)
+                    .ok_or_else(|| format!("track {} indexes are out of order", next.number))?,
+                None => file_size.saturating_sub(byte_start) / size,
+            };
+
+            if track.session > session && !tracks.is_empty() {
+                let lead_in = u64::from(track.pregap) + u64::from(index01 - first_frame);
+                cursor += u64::from(SESSION_GAP_SECTORS).saturating_sub(lead_in);
+            }
+            session = session.max(track.session);
+
+            if track.pregap > 0 {
+                extents.push(Extent {
+                    start_lba: to_lba(cursor)?,
+                    sectors: track.pregap,
+                    mode: track.mode,
+                    source: None,
+                });
+                cursor += u64::from(track.pregap);
+            }
+
+            let start_lba = to_lba(cursor + u64::from(index01 - first_frame))?;
+            if stored_sectors > 0 {
+                extents.push(Extent {
+                    start_lba: to_lba(cursor)?,
+                    sectors: to_lba(stored_sectors)?,
+                    mode: track.mode,
+                    source: Some((file_idx, byte_start)),
+                });
+            }
+            cursor += stored_sectors;
+            byte_start += stored_sectors * size;
+
+            if track.postgap > 0 {
+                extents.push(Extent {
+                    start_lba: to_lba(cursor)?,
+                    sectors: track.postgap,
+                    mode: track.mode,
+                    source: None,
+                });
+                cursor += u64::from(track.postgap);
+            }
+
+            if let Some(previous) = tracks.last_mut() {
+                previous.end_lba = start_lba;
+            }
+            tracks.push(ImageTrack {
+                number: track.number,
+                mode: track.mode,
+                session,
+                start_lba,
+                end_lba: 0,
+            });
+        }
+    }
+
+    let leadout_lba = to_lba(cursor)?;
+    if let Some(last) = tracks.last_mut() {
+        last.end_lba = leadout_lba;
+    }
+    if tracks.iter().any(|track| track.start_lba >= track.end_lba) {
+        return Err("image files are too short for the cue sheet".to_string());
+    }
+
+    Ok(Layout {
+        tracks,
+        extents,
+        leadout_lba,
+    })
+}
+
+fn to_lba(value: u64) -> Result<u32, String> {
+    u32::try_from(value).map_err(|_| "image is too large".to_string())
+}
+
+fn lba_out_of_range(lba: u32, sectors: u32) -> CdReaderError {
+    illegal_request(ScsiOp::ReadCd, Some(lba), Some(sectors), 0x21)
+}
+
+/// Build the CHECK CONDITION a drive reports for an ILLEGAL REQUEST.
+fn illegal_request(op: ScsiOp, lba: Option<u32>, sectors: Option<u32>, asc: u8) -> CdReaderError {
+    CdReaderError::Scsi(ScsiError::check_condition(
+        op,
+        lba,
+        sectors,
+        (0x05, asc, 0x00),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{CdReader, ReadOptions, RetryConfig};
+
+    fn layout(cue: &str, file_sizes: &[u64]) -> Layout {
+        build_layout(&parse_cue_sheet(cue).unwrap(), file_sizes).unwrap()
+    }
+
+    fn starts(layout: &Layout) -> Vec<(u8, u32)> {
+        layout
+            .tracks
+            .iter()
+            .map(|track| (track.number, track.start_lba))
+            .collect()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cd-da-reader-cue-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn mode1_sector(fill: u8) -> Vec<u8> {
+        let mut sector = vec![fill; 2352];
+        sector[..12].copy_from_slice(&[0, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0]);
+        sector[15] = 0x01;
+        sector
+    }
+
+    #[test]
+    fn lays_out_a_single_file_audio_image() {
+        let layout = layout(
+            "FILE \"a.bin\" BINARY\n\
+             TRACK 01 AUDIO\nINDEX 01 00:00:00\n\
+             TRACK 02 AUDIO\nINDEX 00 00:10:00\nINDEX 01 00:12:00\n",
+            &[1_000 * 2352],
+        );
+
+        assert_eq!(starts(&layout), [(1, 0), (2, 900)]);
+        assert_eq!(layout.leadout_lba, 1_000);
+        assert_eq!(
+            layout.extents,
+            [
+                Extent {
+                    start_lba: 0,
+                    sectors: 750,
+                    mode: CueTrackMode::Audio,
+                    source: Some((0, 0)),
+                },
+                Extent {
+                    start_lba: 750,
+                    sectors: 250,
+                    mode: CueTrackMode::Audio,
+                    source: Some((0, 750 * 2352)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn inserts_pregaps_for_mixed_mode_images() {
+        let layout = layout(
+            "FILE \"data.bin\" BINARY\nTRACK 01 MODE1/2048\nINDEX 01 00:00:00\n\
+             FILE \"audio.bin\" BINARY\nTRACK 02 AUDIO\nPREGAP 00:02:00\nINDEX 01 00:00:00\n",
+            &[500 * 2048, 300 * 2352],
+        );
+
+        assert_eq!(starts(&layout), [(1, 0), (2, 650)]);
+        assert_eq!(layout.leadout_lba, 950);
+        assert_eq!(layout.extents[1].source, None);
+        assert_eq!(layout.extents[2].source, Some((1, 0)));
+    }
+
+    #[test]
+    fn adds_the_session_gap_before_cd_extra_data_tracks() {
+        let layout = layout(
+            "FILE \"audio.bin\" BINARY\n\
+             TRACK 01 AUDIO\nINDEX 01 00:00:00\n\
+             TRACK 02 AUDIO\nINDEX 01 00:10:00\n\
+             REM SESSION 02\n\
+             FILE \"data.bin\" BINARY\nTRACK 03 MODE2/2352\nINDEX 01 00:00:00\n",
+            &[2_000 * 2352, 400 * 2352],
+        );
+
+        assert_eq!(starts(&layout), [(1, 0), (2, 750), (3, 2_000 + 11_400)]);
+        assert_eq!(layout.tracks[2].session, 2);
+        assert_eq!(layout.leadout_lba, 2_000 + 11_400 + 400);
+
+        let toc = Toc {
+            first_track: 1,
+            last_track: 3,
+            tracks: layout
+                .tracks
+                .iter()
+                .map(|track| Track {
+                    number: track.number,
+                    start_lba: track.start_lba,
+                    start_msf: lba_to_msf(track.start_lba),
+                    is_audio: track.mode.is_audio(),
+                })
+                .collect(),
+            leadout_lba: layout.leadout_lba,
+        };
+        // the last audio track ends exactly where the audio file ends
+        assert_eq!(
+            crate::utils::get_track_bounds(&toc, 2).unwrap(),
+            (750, 2_000 - 750)
+        );
+    }
+
+    #[test]
+    fn rejects_image_files_shorter_than_the_cue_sheet() {
+        let cue = "FILE \"a.bin\" BINARY\n\
+                   TRACK 01 AUDIO\nINDEX 01 00:00:00\n\
+                   TRACK 02 AUDIO\nINDEX 01 00:10:00\n";
+        assert!(build_layout(&parse_cue_sheet(cue).unwrap(), &[100 * 2352]).is_err());
+    }
+
+    #[test]
+    fn reads_tracks_from_an_image_on_disk() {
+        let dir = temp_dir("read");
+        let mut bin = Vec::new();
+        for _ in 0..10 {
+            bin.extend_from_slice(&mode1_sector(0x11));
+        }
+        bin.extend(std::iter::repeat_n(0x22, 20 * 2352));
+        std::fs::write(dir.join("disc.bin"), &bin).unwrap();
+        std::fs::write(
+            dir.join("disc.cue"),
+            "FILE \"disc.bin\" BINARY\n\
+             TRACK 01 MODE1/2352\nINDEX 01 00:00:00\n\
+             TRACK 02 AUDIO\nINDEX 01 00:00:10\n",
+        )
+        .unwrap();
+
+        let reader = CdReader::from_backend(CueImage::open(dir.join("disc.cue")).unwrap());
+        let toc = reader.read_toc().unwrap();
+        assert_eq!(toc.leadout_lba, 30);
+        assert!(!toc.tracks[0].is_audio);
+        assert_eq!(toc.tracks[1].start_msf, (0, 2, 10));
+
+        let audio = reader.read_track(&toc, 2).unwrap();
+        assert_eq!(audio, vec![0x22; 20 * 2352]);
+
+        assert_eq!(
+            reader.detect_track_format(&toc.tracks[0]).unwrap(),
+            SectorReadFormat::Mode1Cooked
+        );
+        let cooked = reader
+            .read_track_with_options(
+                &toc,
+                1,
+                &ReadOptions::default().with_format(SectorReadFormat::Mode1Cooked),
+            )
+            .unwrap();
+        assert_eq!(cooked, vec![0x11; 10 * 2048]);
+
+        let single_attempt =
+            ReadOptions::default().with_retry(RetryConfig::default().with_max_attempts(1));
+        let err = reader.read_sector_range(0, 1, &single_attempt).unwrap_err();
+        assert!(matches!(
+            err,
+            CdReaderError::Scsi(ScsiError {
+                sense_key: Some(0x05),
+                asc: Some(0x64),
+                ..
+            })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
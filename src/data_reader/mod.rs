@@ -4,18 +4,25 @@ mod sector_read_format;
 pub(crate) mod track_information;
 
 pub use sector_read_format::SectorReadFormat;
+pub use track_information::TrackInformation;
 
 use crate::retry::RetryConfig;
-use crate::{CdReaderError, Track};
+use crate::{CacheDefeat, CdReaderError, ReadSpeed, Track};
 
-/// Sector format and retry options for track and sector-range reads.
+/// Sector format, retry, offset, speed, cache and jitter options for track
+/// and sector-range reads.
 ///
-/// The defaults read audio sectors using the default retry policy. Use the
+/// The defaults read audio sectors using the default retry policy, without
+/// read offset correction, at whatever speed the drive is set to. Use the
 /// builder methods to override only the options you need.
 #[derive(Debug, Clone)]
 pub struct ReadOptions {
     format: SectorReadFormat,
     retry: RetryConfig,
+    read_offset: i32,
+    read_speed: Option<ReadSpeed>,
+    cache_defeat: Option<CacheDefeat>,
+    jitter_correction: bool,
 }
 
 impl ReadOptions {
@@ -31,6 +38,53 @@ impl ReadOptions {
         self
     }
 
+    /// Set the drive's read offset correction, in samples.
+    ///
+    /// Reads are shifted by this many stereo samples (4 bytes each), so that
+    /// the returned audio matches what a drive without an offset would
+    /// return. Use the same value as in other rippers, for example `+6` for
+    /// many current drives. Samples before LBA 0 and past the end of the
+    /// audio session that the drive cannot read are returned as silence.
+    ///
+    /// Only [`SectorReadFormat::Audio`] reads can be shifted: a non-zero
+    /// offset with any other format is rejected when reading.
+    pub fn with_read_offset(mut self, samples: i32) -> Self {
+        self.read_offset = samples;
+        self
+    }
+
+    /// Set the drive's read speed before reading.
+    ///
+    /// Slower speeds reduce read errors on damaged discs, see
+    /// [`CdReader::set_read_speed`](crate::CdReader::set_read_speed). The
+    /// speed stays in effect after the read.
+    pub fn with_read_speed(mut self, speed: ReadSpeed) -> Self {
+        self.read_speed = Some(speed);
+        self
+    }
+
+    /// Defeat the drive's read cache before the re-reads of secure reads,
+    /// such as [`CdReader::read_track_secure`](crate::CdReader::read_track_secure),
+    /// so that the sectors are read from the disc again. Other reads ignore
+    /// it.
+    pub fn with_cache_defeat(mut self, defeat: CacheDefeat) -> Self {
+        self.cache_defeat = Some(defeat);
+        self
+    }
+
+    /// Overlap consecutive reads and stitch them where their audio matches,
+    /// for drives without accurate streaming
+    /// ([`DriveCapabilities::accurate_stream`](crate::DriveCapabilities::accurate_stream)).
+    ///
+    /// Those drives can start each read a few samples early or late, which
+    /// causes clicks at chunk boundaries. Correction reads about 8% more
+    /// sectors, and only [`SectorReadFormat::Audio`] reads can be corrected.
+    /// Secure reads do not apply it.
+    pub fn with_jitter_correction(mut self, enabled: bool) -> Self {
+        self.jitter_correction = enabled;
+        self
+    }
+
     pub(crate) fn format(&self) -> SectorReadFormat {
         self.format
     }
@@ -38,6 +92,22 @@ impl ReadOptions {
     pub(crate) fn retry(&self) -> &RetryConfig {
         &self.retry
     }
+
+    pub(crate) fn read_offset(&self) -> i32 {
+        self.read_offset
+    }
+
+    pub(crate) fn read_speed(&self) -> Option<ReadSpeed> {
+        self.read_speed
+    }
+
+    pub(crate) fn cache_defeat(&self) -> Option<CacheDefeat> {
+        self.cache_defeat
+    }
+
+    pub(crate) fn jitter_correction(&self) -> bool {
+        self.jitter_correction
+    }
 }
 
 impl Default for ReadOptions {
@@ -45,6 +115,10 @@ impl Default for ReadOptions {
         Self {
             format: SectorReadFormat::Audio,
             retry: RetryConfig::default(),
+            read_offset: 0,
+            read_speed: None,
+            cache_defeat: None,
+            jitter_correction: false,
         }
     }
 }
@@ -65,8 +139,7 @@ pub(crate) fn validate_track_format(
     })
 }
 
-/// Build a READ CD (0xBE) command descriptor block for Linux and Windows.
-#[cfg(any(target_os = "linux", target_os = "windows", test))]
+/// Build a READ CD (0xBE) command descriptor block.
 pub(crate) fn build_read_cd_cdb(lba: u32, sectors: u32, format: SectorReadFormat) -> [u8; 12] {
     let mut cdb = [0u8; 12];
     cdb[0] = 0xBE;
@@ -91,10 +164,12 @@ mod tests {
         let retry = crate::RetryConfig::default().with_max_attempts(9);
         let options = ReadOptions::default()
             .with_format(SectorReadFormat::Mode1Raw)
-            .with_retry(retry);
+            .with_retry(retry)
+            .with_read_offset(-12);
 
         assert_eq!(options.format(), SectorReadFormat::Mode1Raw);
         assert_eq!(options.retry().max_attempts, 9);
+        assert_eq!(options.read_offset(), -12);
     }
 
     #[test]
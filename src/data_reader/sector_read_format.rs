@@ -1,8 +1,18 @@
+use crate::c2::C2_POINTERS_SIZE;
+
 /// Sector format requested through the READ CD (0xBE) command.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SectorReadFormat {
     /// CD-DA audio: 2352 bytes of PCM per sector.
     Audio,
+    /// CD-DA audio followed by the sector's C2 error pointers: 2352 bytes of
+    /// PCM and a 294-byte bitmap of the bytes the drive could not correct,
+    /// 2646 bytes per sector. See [`C2Read`](crate::C2Read) to split them.
+    ///
+    /// Only drives reporting
+    /// [`DriveCapabilities::c2_pointers`](crate::DriveCapabilities::c2_pointers)
+    /// support it, and read offset correction is not available with it.
+    AudioWithC2,
     /// Mode 1 user data only: 2048 bytes per sector.
     Mode1Cooked,
     /// Complete Mode 1 sector: 2352 bytes with sync, header, user data, EDC,
@@ -17,22 +27,22 @@ pub enum SectorReadFormat {
 
 impl SectorReadFormat {
     pub(crate) fn is_audio(&self) -> bool {
-        matches!(self, Self::Audio)
+        matches!(self, Self::Audio | Self::AudioWithC2)
     }
 
     /// Bytes returned per sector for this format.
     pub fn sector_size(&self) -> usize {
         match self {
             Self::Audio | Self::Mode1Raw | Self::Mode2Raw => 2352,
+            Self::AudioWithC2 => 2352 + C2_POINTERS_SIZE,
             Self::Mode1Cooked => 2048,
         }
     }
 
     /// CDB byte 1: Expected Sector Type in bits 4–2.
-    #[cfg(any(target_os = "linux", target_os = "windows", test))]
     pub(crate) fn cdb_byte1(&self) -> u8 {
         match self {
-            Self::Audio => 0x04,
+            Self::Audio | Self::AudioWithC2 => 0x04,
             Self::Mode1Cooked | Self::Mode1Raw => 0x08,
             // Mode 2 forms can be interleaved, so let the drive determine the
             // actual sector type while returning the complete sector.
@@ -41,10 +51,11 @@ impl SectorReadFormat {
     }
 
     /// CDB byte 9: Main Channel Selection.
-    #[cfg(any(target_os = "linux", target_os = "windows", test))]
     pub(crate) fn cdb_byte9(&self) -> u8 {
         match self {
             Self::Audio | Self::Mode1Cooked => 0x10,
+            // user data and the C2 error bit per byte
+            Self::AudioWithC2 => 0x12,
             Self::Mode1Raw | Self::Mode2Raw => 0xF8,
         }
     }
@@ -65,6 +76,7 @@ mod tests {
     #[test]
     fn expected_sector_types_are_encoded_in_cdb_byte1() {
         assert_eq!(SectorReadFormat::Audio.cdb_byte1(), 0x04);
+        assert_eq!(SectorReadFormat::AudioWithC2.cdb_byte1(), 0x04);
         assert_eq!(SectorReadFormat::Mode1Cooked.cdb_byte1(), 0x08);
         assert_eq!(SectorReadFormat::Mode1Raw.cdb_byte1(), 0x08);
         assert_eq!(SectorReadFormat::Mode2Raw.cdb_byte1(), 0x00);
@@ -73,6 +85,7 @@ mod tests {
     #[test]
     fn main_channel_fields_are_encoded_in_cdb_byte9() {
         assert_eq!(SectorReadFormat::Audio.cdb_byte9(), 0x10);
+        assert_eq!(SectorReadFormat::AudioWithC2.cdb_byte9(), 0x12);
         assert_eq!(SectorReadFormat::Mode1Cooked.cdb_byte9(), 0x10);
         assert_eq!(SectorReadFormat::Mode1Raw.cdb_byte9(), 0xF8);
         assert_eq!(SectorReadFormat::Mode2Raw.cdb_byte9(), 0xF8);
@@ -81,6 +94,7 @@ mod tests {
     #[test]
     fn sector_sizes_match_mmc_layouts() {
         assert_eq!(SectorReadFormat::Audio.sector_size(), 2352);
+        assert_eq!(SectorReadFormat::AudioWithC2.sector_size(), 2646);
         assert_eq!(SectorReadFormat::Mode1Cooked.sector_size(), 2048);
         assert_eq!(SectorReadFormat::Mode1Raw.sector_size(), 2352);
         assert_eq!(SectorReadFormat::Mode2Raw.sector_size(), 2352);
@@ -90,6 +104,7 @@ mod tests {
     fn transfer_caps_stay_within_64_kib() {
         for format in [
             SectorReadFormat::Audio,
+            SectorReadFormat::AudioWithC2,
             SectorReadFormat::Mode1Cooked,
             SectorReadFormat::Mode1Raw,
             SectorReadFormat::Mode2Raw,
@@ -1,22 +1,25 @@
 use std::io;
 
-#[cfg(any(target_os = "linux", target_os = "windows", test))]
 pub(crate) const TRACK_INFORMATION_RESPONSE_SIZE: usize = 36;
 
 /// Track-level metadata returned by MMC READ TRACK INFORMATION (0x52).
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) struct TrackInformation {
-    pub(crate) track_number: u16,
-    pub(crate) session_number: u16,
-    pub(crate) track_mode: u8,
-    pub(crate) data_mode: u8,
-    pub(crate) start_lba: u32,
-    pub(crate) track_size: u32,
+pub struct TrackInformation {
+    /// Track number, including the MMC-6 most significant byte.
+    pub track_number: u16,
+    /// Session number, including the MMC-6 most significant byte.
+    pub session_number: u16,
+    /// Track Mode nibble, which mirrors the TOC control field.
+    pub track_mode: u8,
+    /// Data Mode nibble: `0x01` for Mode 1, `0x02` for Mode 2.
+    pub data_mode: u8,
+    /// Starting logical block address of the track.
+    pub start_lba: u32,
+    /// Track size in sectors.
+    pub track_size: u32,
 }
 
 /// Build READ TRACK INFORMATION with the address interpreted as a track number.
-#[cfg(any(target_os = "linux", target_os = "windows", test))]
 pub(crate) fn build_read_track_information_cdb(
     track_number: u8,
     allocation_length: u16,
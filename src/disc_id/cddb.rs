@@ -0,0 +1,71 @@
+use crate::Toc;
+
+impl Toc {
+    /// Compute the classic 32-bit FreeDB/CDDB disc ID.
+    ///
+    /// Unlike the MusicBrainz ID, CDDB covers every track in the TOC,
+    /// including CD-Extra data tracks, and uses the disc's final lead-out.
+    pub fn cddb_disc_id(&self) -> u32 {
+        let checksum: u32 = self
+            .tracks
+            .iter()
+            .map(|track| digit_sum(frame_offset(track.start_lba) / 75))
+            .sum();
+        let first_second = self
+            .tracks
+            .first()
+            .map_or(0, |track| frame_offset(track.start_lba) / 75);
+        let length = (frame_offset(self.leadout_lba) / 75).saturating_sub(first_second);
+
+        ((checksum % 255) << 24) | (length << 8) | self.tracks.len() as u32
+    }
+
+    /// Build the `cddb query` command used by FreeDB-compatible servers such
+    /// as GnuDB: disc ID, track count, frame offset of every track and the
+    /// total disc length in seconds.
+    pub fn cddb_query(&self) -> String {
+        let mut query = format!(
+            "cddb query {:08x} {}",
+            self.cddb_disc_id(),
+            self.tracks.len()
+        );
+        for track in &self.tracks {
+            query.push_str(&format!(" {}", frame_offset(track.start_lba)));
+        }
+        query.push_str(&format!(" {}", frame_offset(self.leadout_lba) / 75));
+        query
+    }
+}
+
+/// LBA as an absolute frame count, including the 2-second lead-in.
+fn frame_offset(lba: u32) -> u32 {
+    lba + 150
+}
+
+fn digit_sum(mut value: u32) -> u32 {
+    let mut sum = 0;
+    while value > 0 {
+        sum += value % 10;
+        value /= 10;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::sample_toc;
+
+    #[test]
+    fn computes_cddb_disc_id() {
+        assert_eq!(sample_toc().cddb_disc_id(), 0x990aab0b);
+    }
+
+    #[test]
+    fn builds_cddb_query() {
+        assert_eq!(
+            sample_toc().cddb_query(),
+            "cddb query 990aab0b 11 150 13282 28117 47614 63175 90570 104292 126875 \
+             140037 164402 179635 2733"
+        );
+    }
+}
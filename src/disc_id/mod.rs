@@ -0,0 +1,10 @@
+//! Disc identifiers computed from the Table of Contents, used to look up
+//! release metadata in online databases.
+
+mod cddb;
+mod musicbrainz;
+mod sha1;
+
+pub use musicbrainz::MusicBrainzDiscId;
+pub(crate) use musicbrainz::musicbrainz_base64;
+pub(crate) use sha1::sha1;
@@ -0,0 +1,177 @@
+use super::sha1::sha1;
+use crate::{CdReaderError, Toc, utils};
+
+const SUBMISSION_URL: &str = "https://musicbrainz.org/cdtoc/attach";
+
+/// MusicBrainz disc ID together with the TOC it was computed from.
+///
+/// See the [MusicBrainz disc ID algorithm](https://musicbrainz.org/doc/Disc_ID_Calculation).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MusicBrainzDiscId {
+    id: String,
+    first_track: u8,
+    last_track: u8,
+    /// Lead-out and track offsets in frames, including the 150-frame lead-in.
+    leadout_offset: u32,
+    track_offsets: Vec<u32>,
+}
+
+impl MusicBrainzDiscId {
+    /// The 28-character disc ID, for example `49HHV7Eb8UKF3aQiNmu1GR8vKTY-`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// TOC in the `first last leadout offset1 offset2 ...` form used by the
+    /// MusicBrainz web service (`toc` parameter of the `discid` lookup).
+    pub fn toc_string(&self) -> String {
+        let mut values = vec![
+            self.first_track.to_string(),
+            self.last_track.to_string(),
+            self.leadout_offset.to_string(),
+        ];
+        values.extend(self.track_offsets.iter().map(u32::to_string));
+        values.join(" ")
+    }
+
+    /// URL that lets a user attach this disc ID to a release on MusicBrainz.
+    pub fn submission_url(&self) -> String {
+        format!(
+            "{SUBMISSION_URL}?id={}&tracks={}&toc={}",
+            self.id,
+            self.track_offsets.len(),
+            self.toc_string().replace(' ', "+")
+        )
+    }
+}
+
+impl Toc {
+    /// Compute the MusicBrainz disc ID of this TOC.
+    ///
+    /// Only the first session is identified: on CD-Extra discs the trailing
+    /// data tracks are left out and the lead-out is moved to the end of the
+    /// last audio track, as the algorithm requires.
+    pub fn musicbrainz_disc_id(&self) -> Result<MusicBrainzDiscId, CdReaderError> {
+        let (tracks, leadout_lba) = utils::first_session_tracks(self)?;
+        if tracks.iter().any(|track| !(1..=99).contains(&track.number)) {
+            return Err(CdReaderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "track numbers must be between 1 and 99",
+            )));
+        }
+
+        let first_track = tracks[0].number;
+        let last_track = tracks[tracks.len() - 1].number;
+        let leadout_offset = leadout_lba + 150;
+        let track_offsets: Vec<u32> = tracks.iter().map(|track| track.start_lba + 150).collect();
+
+        let mut offsets = [0u32; 99];
+        for track in tracks {
+            offsets[usize::from(track.number) - 1] = track.start_lba + 150;
+        }
+
+        let mut input = format!("{first_track:02X}{last_track:02X}{leadout_offset:08X}");
+        for offset in offsets {
+            input.push_str(&format!("{offset:08X}"));
+        }
+
+        Ok(MusicBrainzDiscId {
+            id: musicbrainz_base64(&sha1(input.as_bytes())),
+            first_track,
+            last_track,
+            leadout_offset,
+            track_offsets,
+        })
+    }
+}
+
+/// Base64 with the URL-safe substitutions MusicBrainz uses: `+` → `.`,
+/// `/` → `_` and `=` → `-`.
+pub(crate) fn musicbrainz_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789._";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((group >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('-');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::musicbrainz_base64;
+    use crate::Toc;
+    use crate::test_support::toc;
+
+    #[test]
+    fn computes_the_documented_example_disc_id() {
+        let offsets = [150, 15363, 32314, 46592, 63414, 80489];
+        let tracks: Vec<_> = offsets
+            .iter()
+            .enumerate()
+            .map(|(idx, offset)| (idx as u8 + 1, offset - 150, true))
+            .collect();
+
+        let disc_id = toc(&tracks, 95462 - 150).musicbrainz_disc_id().unwrap();
+
+        assert_eq!(disc_id.id(), "49HHV7Eb8UKF3aQiNmu1GR8vKTY-");
+        assert_eq!(
+            disc_id.toc_string(),
+            "1 6 95462 150 15363 32314 46592 63414 80489"
+        );
+        assert_eq!(
+            disc_id.submission_url(),
+            "https://musicbrainz.org/cdtoc/attach?id=49HHV7Eb8UKF3aQiNmu1GR8vKTY-&tracks=6\
+             &toc=1+6+95462+150+15363+32314+46592+63414+80489"
+        );
+    }
+
+    #[test]
+    fn excludes_cd_extra_data_session() {
+        let disc_id = toc(
+            &[
+                (1, 0, true),
+                (2, 10_000, true),
+                (3, 20_000, true),
+                (4, 41_400, false),
+            ],
+            60_000,
+        )
+        .musicbrainz_disc_id()
+        .unwrap();
+
+        assert_eq!(disc_id.toc_string(), "1 3 30150 150 10150 20150");
+        assert_eq!(disc_id.id(), "O_hH08fblhguAniHOwM4m3781Qc-");
+    }
+
+    #[test]
+    fn rejects_empty_tocs() {
+        let empty = Toc {
+            first_track: 1,
+            last_track: 1,
+            tracks: Vec::new(),
+            leadout_lba: 0,
+        };
+
+        assert!(empty.musicbrainz_disc_id().is_err());
+    }
+
+    #[test]
+    fn encodes_musicbrainz_base64_padding() {
+        assert_eq!(musicbrainz_base64(b"\xfb\xff"), "._8-");
+        assert_eq!(musicbrainz_base64(b"abc"), "YWJj");
+        assert_eq!(musicbrainz_base64(b"a"), "YQ--");
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{CdReader, CdReaderError};
+use std::fmt;
+
+use crate::{CdReader, CdReaderError, InquiryData};
 
 /// Information about all found drives. This info is not tested extensively, and in
 /// general it is encouraged to provide a disk drive directly.
@@ -9,29 +11,33 @@ pub struct DriveInfo {
     pub path: String,
     /// Whether the current disc appears to contain at least one audio track.
     pub has_audio_cd: bool,
+    /// Drive identification from INQUIRY, if the drive could be queried. On
+    /// Linux this falls back to the identification cached in sysfs, so it is
+    /// also available for drives that cannot be opened.
+    pub inquiry: Option<InquiryData>,
+}
+
+impl fmt::Display for DriveInfo {
+    /// Formats as the path followed by the drive model, for example
+    /// `/dev/sr0 (PLEXTOR DVDR   PX-716A 1.11)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)?;
+        if let Some(inquiry) = &self.inquiry {
+            write!(f, " ({inquiry})")?;
+        }
+        Ok(())
+    }
 }
 
 impl CdReader {
-    /// Enumerate candidate optical drives and probe whether they currently have an audio CD.
+    /// Enumerate candidate optical drives, identify them and probe whether they
+    /// currently have an audio CD.
     pub fn list_drives() -> Result<Vec<DriveInfo>, CdReaderError> {
         let mut paths = crate::platform::list_drive_paths()?;
         paths.sort();
         paths.dedup();
 
-        let mut drives = Vec::with_capacity(paths.len());
-        for path in paths {
-            let has_audio_cd = match Self::open_path(&path) {
-                Ok(reader) => match reader.read_toc() {
-                    Ok(toc) => toc.tracks.iter().any(|track| track.is_audio),
-                    Err(_) => false,
-                },
-                Err(_) => false,
-            };
-
-            drives.push(DriveInfo { path, has_audio_cd });
-        }
-
-        Ok(drives)
+        Ok(paths.into_iter().map(probe_drive).collect())
     }
 
     /// Open the first discovered drive that currently has an audio CD.
@@ -43,6 +49,24 @@ impl CdReader {
     }
 }
 
+/// Identify the drive at `path` and check whether it has an audio CD.
+pub(crate) fn probe_drive(path: String) -> DriveInfo {
+    let reader = CdReader::open_path(&path).ok();
+    let has_audio_cd = match reader.as_ref().map(|reader| reader.read_toc()) {
+        Some(Ok(toc)) => toc.tracks.iter().any(|track| track.is_audio),
+        _ => false,
+    };
+    let inquiry = reader.as_ref().and_then(|reader| reader.inquiry().ok());
+    #[cfg(target_os = "linux")]
+    let inquiry = inquiry.or_else(|| crate::platform::sysfs_inquiry(&path));
+
+    DriveInfo {
+        path,
+        has_audio_cd,
+        inquiry,
+    }
+}
+
 fn pick_default_drive(drives: &[DriveInfo]) -> Option<&DriveInfo> {
     drives.iter().find(|drive| drive.has_audio_cd)
 }
@@ -50,6 +74,7 @@ fn pick_default_drive(drives: &[DriveInfo]) -> Option<&DriveInfo> {
 #[cfg(test)]
 mod tests {
     use super::{DriveInfo, pick_default_drive};
+    use crate::InquiryData;
 
     #[test]
     fn chooses_first_audio_drive() {
@@ -57,14 +82,17 @@ mod tests {
             DriveInfo {
                 path: "disk10".to_string(),
                 has_audio_cd: false,
+                inquiry: None,
             },
             DriveInfo {
                 path: "disk11".to_string(),
                 has_audio_cd: true,
+                inquiry: None,
             },
             DriveInfo {
                 path: "disk12".to_string(),
                 has_audio_cd: true,
+                inquiry: None,
             },
         ];
 
@@ -79,8 +107,27 @@ mod tests {
         let drives = vec![DriveInfo {
             path: "/dev/sr0".to_string(),
             has_audio_cd: false,
+            inquiry: None,
         }];
 
         assert!(pick_default_drive(&drives).is_none());
     }
+
+    #[test]
+    fn displays_path_with_drive_model() {
+        let mut drive = DriveInfo {
+            path: "/dev/sr1".to_string(),
+            has_audio_cd: false,
+            inquiry: None,
+        };
+        assert_eq!(drive.to_string(), "/dev/sr1");
+
+        drive.inquiry = Some(InquiryData {
+            peripheral_device_type: 0x05,
+            vendor: "HL-DT-ST".to_string(),
+            product: "DVDRAM GH24NSD1".to_string(),
+            revision: String::new(),
+        });
+        assert_eq!(drive.to_string(), "/dev/sr1 (HL-DT-ST DVDRAM GH24NSD1)");
+    }
 }
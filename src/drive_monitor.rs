@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::discovery::probe_drive;
+use crate::{CdReaderError, DriveInfo};
+
+type Scanner = Box<dyn FnMut() -> io::Result<Vec<String>> + Send>;
+type Prober = Box<dyn FnMut(String) -> DriveInfo + Send>;
+
+/// Optical drive being connected or disconnected.
+#[derive(Debug, Clone)]
+pub enum DriveEvent {
+    /// A drive appeared, probed like in [`CdReader::list_drives`](crate::CdReader::list_drives).
+    Added(DriveInfo),
+    /// A drive disappeared. Holds the information from when it was added.
+    Removed(DriveInfo),
+}
+
+/// Watches for optical drives being connected and disconnected, for example
+/// USB drives being plugged in.
+///
+/// The monitor rescans the system's drives every `interval` (on Linux,
+/// `/sys/class/block`) and reports the differences. Drives present when it
+/// is created are not reported; see [`DriveMonitor::drives`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use cd_da_reader::{DriveEvent, DriveMonitor};
+///
+/// let monitor = DriveMonitor::new(Duration::from_secs(1))?;
+/// for event in monitor {
+///     match event? {
+///         DriveEvent::Added(drive) => println!("connected: {drive}"),
+///         DriveEvent::Removed(drive) => println!("disconnected: {drive}"),
+///     }
+/// }
+/// # Ok::<(), cd_da_reader::CdReaderError>(())
+/// ```
+pub struct DriveMonitor {
+    interval: Duration,
+    known: BTreeMap<String, DriveInfo>,
+    pending: VecDeque<DriveEvent>,
+    scan: Scanner,
+    probe: Prober,
+}
+
+impl DriveMonitor {
+    /// Scan the current drives and start watching for changes.
+    pub fn new(interval: Duration) -> Result<Self, CdReaderError> {
+        Self::with_scanner(
+            interval,
+            Box::new(crate::platform::list_drive_paths),
+            Box::new(probe_drive),
+        )
+    }
+
+    fn with_scanner(
+        interval: Duration,
+        mut scan: Scanner,
+        mut probe: Prober,
+    ) -> Result<Self, CdReaderError> {
+        let known = scan()?
+            .into_iter()
+            .map(|path| (path.clone(), probe(path)))
+            .collect();
+
+        Ok(Self {
+            interval,
+            known,
+            pending: VecDeque::new(),
+            scan,
+            probe,
+        })
+    }
+
+    /// Drives currently connected, as of the last scan.
+    pub fn drives(&self) -> impl Iterator<Item = &DriveInfo> {
+        self.known.values()
+    }
+
+    /// Rescan once, without waiting, and return the changes since the
+    /// previous scan.
+    pub fn poll(&mut self) -> Result<Vec<DriveEvent>, CdReaderError> {
+        let mut paths = (self.scan)()?;
+        paths.sort();
+        paths.dedup();
+
+        let removed: Vec<String> = self
+            .known
+            .keys()
+            .filter(|path| paths.binary_search(path).is_err())
+            .cloned()
+            .collect();
+        let mut events: Vec<DriveEvent> = removed
+            .into_iter()
+            .filter_map(|path| self.known.remove(&path))
+            .map(DriveEvent::Removed)
+            .collect();
+
+        for path in paths {
+            if !self.known.contains_key(&path) {
+                let drive = (self.probe)(path.clone());
+                self.known.insert(path, drive.clone());
+                events.push(DriveEvent::Added(drive));
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+impl Iterator for DriveMonitor {
+    type Item = Result<DriveEvent, CdReaderError>;
+
+    /// Block until the next drive is connected or disconnected.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+
+            sleep(self.interval);
+            match self.poll() {
+                Ok(events) => self.pending.extend(events),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    use super::{DriveEvent, DriveMonitor};
+    use crate::DriveInfo;
+
+    fn monitor(scans: Vec<Vec<&str>>) -> DriveMonitor {
+        let mut scans: VecDeque<Vec<String>> = scans
+            .into_iter()
+            .map(|paths| paths.into_iter().map(str::to_string).collect())
+            .collect();
+        DriveMonitor::with_scanner(
+            Duration::ZERO,
+            Box::new(move || Ok(scans.pop_front().unwrap_or_default())),
+            Box::new(|path| DriveInfo {
+                path,
+                has_audio_cd: false,
+                inquiry: None,
+            }),
+        )
+        .unwrap()
+    }
+
+    fn describe(event: &DriveEvent) -> String {
+        match event {
+            DriveEvent::Added(drive) => format!("+{}", drive.path),
+            DriveEvent::Removed(drive) => format!("-{}", drive.path),
+        }
+    }
+
+    #[test]
+    fn reports_added_and_removed_drives() {
+        let mut monitor = monitor(vec![
+            vec!["/dev/sr0"],
+            vec!["/dev/sr0", "/dev/sr1"],
+            vec!["/dev/sr1"],
+            vec!["/dev/sr1"],
+        ]);
+        assert_eq!(monitor.drives().count(), 1);
+
+        let events: Vec<_> = monitor.poll().unwrap().iter().map(describe).collect();
+        assert_eq!(events, vec!["+/dev/sr1"]);
+        let events: Vec<_> = monitor.poll().unwrap().iter().map(describe).collect();
+        assert_eq!(events, vec!["-/dev/sr0"]);
+        assert!(monitor.poll().unwrap().is_empty());
+        assert_eq!(
+            monitor
+                .drives()
+                .map(|drive| drive.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/dev/sr1"]
+        );
+    }
+
+    #[test]
+    fn iterator_waits_for_the_next_change() {
+        let monitor = monitor(vec![
+            vec![],
+            vec![],
+            vec!["/dev/sr0"],
+            vec!["/dev/sr0"],
+            vec![],
+        ]);
+
+        let events: Vec<_> = monitor
+            .take(2)
+            .map(|event| describe(&event.unwrap()))
+            .collect();
+
+        assert_eq!(events, vec!["+/dev/sr0", "-/dev/sr0"]);
+    }
+}
@@ -0,0 +1,113 @@
+//! Known drive read offsets, keyed by INQUIRY vendor and product.
+//!
+//! Values follow the AccurateRip drive offset list, in samples. Only drives
+//! whose offset is well established are included; for anything else, measure
+//! the offset with a key disc and pass it to
+//! [`ReadOptions::with_read_offset`](crate::ReadOptions::with_read_offset).
+
+use crate::InquiryData;
+
+/// `(vendor, product, read offset in samples)`.
+///
+/// Products are matched as a whole word prefix, so `DRW-24B1ST` also matches
+/// the `DRW-24B1ST   a` product string some firmware revisions report.
+const DRIVE_OFFSETS: &[(&str, &str, i32)] = &[
+    ("ASUS", "BW-16D1HT", 6),
+    ("ASUS", "DRW-24B1ST", 6),
+    ("ASUS", "DRW-24D5MT", 6),
+    ("HL-DT-ST", "BD-RE WH16NS40", 6),
+    ("HL-DT-ST", "DVDRAM GH24NSB0", 6),
+    ("HL-DT-ST", "DVDRAM GH24NSD1", 6),
+    ("Optiarc", "DVD RW AD-7240S", 48),
+    ("Optiarc", "DVD RW AD-7280S", 48),
+    ("PIONEER", "DVD-RW DVR-111D", 48),
+    ("PLEXTOR", "CD-R PREMIUM", 30),
+    ("PLEXTOR", "DVDR PX-712A", 30),
+    ("PLEXTOR", "DVDR PX-716A", 30),
+    ("PLEXTOR", "DVDR PX-755A", 30),
+    ("PLEXTOR", "DVDR PX-760A", 30),
+    ("TSSTcorp", "CDDVDW SH-224BB", 6),
+    ("TSSTcorp", "CDDVDW SH-224DB", 6),
+    ("TSSTcorp", "CDDVDW SH-S223C", 6),
+    ("YAMAHA", "CRW-F1", 733),
+];
+
+/// Look up the read offset of a drive model, in samples.
+///
+/// Vendor and product are compared case-insensitively with runs of
+/// whitespace collapsed, so the raw space-padded INQUIRY strings can be
+/// passed directly.
+pub fn lookup_drive_offset(vendor: &str, product: &str) -> Option<i32> {
+    let vendor = normalize(vendor);
+    let product = normalize(product);
+
+    DRIVE_OFFSETS
+        .iter()
+        .find(|(known_vendor, known_product, _)| {
+            let known_product = normalize(known_product);
+            normalize(known_vendor) == vendor
+                && product
+                    .strip_prefix(&known_product)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+        })
+        .map(|(_, _, offset)| *offset)
+}
+
+impl InquiryData {
+    /// Read offset of this drive model from the built-in table, if known.
+    pub fn known_read_offset(&self) -> Option<i32> {
+        lookup_drive_offset(&self.vendor, &self.product)
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lookup_drive_offset;
+    use crate::{CdReader, InquiryData, MockDrive};
+
+    #[test]
+    fn matches_padded_inquiry_strings() {
+        assert_eq!(
+            lookup_drive_offset("PLEXTOR ", "DVDR   PX-716A  "),
+            Some(30)
+        );
+        assert_eq!(lookup_drive_offset("hl-dt-st", "dvdram gh24nsb0"), Some(6));
+        assert_eq!(lookup_drive_offset("YAMAHA", "CRW-F1"), Some(733));
+    }
+
+    #[test]
+    fn matches_products_with_a_revision_suffix_only() {
+        assert_eq!(lookup_drive_offset("ASUS", "DRW-24B1ST   a"), Some(6));
+        assert_eq!(lookup_drive_offset("ASUS", "DRW-24B1STX"), None);
+        assert_eq!(lookup_drive_offset("ASUS", "DRW-24"), None);
+    }
+
+    #[test]
+    fn returns_none_for_unknown_drives() {
+        assert_eq!(lookup_drive_offset("PLEXTOR", "DVDR PX-999Z"), None);
+        assert_eq!(lookup_drive_offset("ACME", "DVDR PX-716A"), None);
+    }
+
+    #[test]
+    fn reader_looks_up_the_offset_of_the_opened_drive() {
+        let drive = MockDrive::audio_cd(&[10]);
+        drive.set_inquiry(InquiryData {
+            peripheral_device_type: 0x05,
+            vendor: "PLEXTOR".to_string(),
+            product: "DVDR   PX-716A".to_string(),
+            revision: "1.11".to_string(),
+        });
+
+        let reader = CdReader::from_backend(drive);
+
+        assert_eq!(reader.known_read_offset().unwrap(), Some(30));
+    }
+}
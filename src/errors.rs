@@ -11,12 +11,36 @@ pub enum ScsiOp {
     ReadCd,
     /// `READ TRACK INFORMATION` command (opcode `0x52`) for track metadata.
     ReadTrackInformation,
+    /// `INQUIRY` command (opcode `0x12`) for drive identification.
+    Inquiry,
+    /// `MODE SENSE(10)` command (opcode `0x5A`) for the capabilities page.
+    ModeSense,
+    /// `GET CONFIGURATION` command (opcode `0x46`) for MMC features.
+    GetConfiguration,
+    /// `START STOP UNIT` command (opcode `0x1B`) to eject or load the disc.
+    StartStopUnit,
+    /// `PREVENT ALLOW MEDIUM REMOVAL` command (opcode `0x1E`) to lock or
+    /// unlock the tray.
+    PreventAllowMediumRemoval,
+    /// `SET CD SPEED` command (opcode `0xBB`) to limit the read speed.
+    SetCdSpeed,
+    /// `SET STREAMING` command (opcode `0xB6`) to limit the read speed.
+    SetStreaming,
+    /// `TEST UNIT READY` command (opcode `0x00`) to check for a readable disc.
+    TestUnitReady,
+    /// `GET EVENT STATUS NOTIFICATION` command (opcode `0x4A`) for media
+    /// events.
+    GetEventStatusNotification,
+    /// `READ(10)` command (opcode `0x28`) with Force Unit Access, to drop
+    /// cached sectors.
+    Read10,
 }
 
 /// Structured SCSI failure context captured at the call site.
 ///
 /// This keeps transport/protocol details (status + sense) separate from plain I/O failures,
 /// which allows retry logic and application diagnostics to branch on SCSI metadata.
+/// [`ScsiError::describe`] decodes the sense data into readable text.
 #[derive(Debug, Clone)]
 pub struct ScsiError {
     /// Operation that failed.
@@ -35,6 +59,38 @@ pub struct ScsiError {
     pub ascq: Option<u8>,
 }
 
+impl ScsiError {
+    /// CHECK CONDITION with fixed-format sense data, as reported by a drive.
+    pub(crate) fn check_condition(
+        op: ScsiOp,
+        lba: Option<u32>,
+        sectors: Option<u32>,
+        sense: (u8, u8, u8),
+    ) -> Self {
+        let (sense_key, asc, ascq) = sense;
+        Self {
+            op,
+            lba,
+            sectors,
+            scsi_status: 0x02,
+            sense_key: Some(sense_key),
+            asc: Some(asc),
+            ascq: Some(ascq),
+        }
+    }
+}
+
+/// Extract sense key, ASC and ASCQ from fixed-format sense data.
+///
+/// `written` is the number of sense bytes the transport reported as valid.
+pub(crate) fn parse_fixed_sense(sense: &[u8], written: u8) -> (Option<u8>, Option<u8>, Option<u8>) {
+    if written < 14 || sense.len() < 14 {
+        return (None, None, None);
+    }
+
+    (Some(sense[2] & 0x0F), Some(sense[12]), Some(sense[13]))
+}
+
 /// Top-level error type returned by `cd-da-reader`.
 #[derive(Debug)]
 pub enum CdReaderError {
@@ -68,11 +124,30 @@ impl fmt::Display for CdReaderError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Io(err) => write!(f, "io error: {err}"),
-            Self::Scsi(err) => write!(
-                f,
-                "SCSI {:?} failed (status=0x{:02x}, lba={:?}, sectors={:?}, sense_key={:?}, asc={:?}, ascq={:?})",
-                err.op, err.scsi_status, err.lba, err.sectors, err.sense_key, err.asc, err.ascq
-            ),
+            Self::Scsi(err) => {
+                write!(f, "SCSI {:?} failed", err.op)?;
+                if let Some(lba) = err.lba {
+                    write!(f, " at LBA {lba}")?;
+                }
+                if let Some(sectors) = err.sectors {
+                    write!(f, " ({sectors} sectors)")?;
+                }
+                let Some(description) = err.describe() else {
+                    return write!(
+                        f,
+                        " with status 0x{:02x} and no sense data",
+                        err.scsi_status
+                    );
+                };
+                let hex = |code: Option<u8>| code.map_or("??".to_string(), |c| format!("{c:02X}"));
+                write!(
+                    f,
+                    ": {description} (sense {}/{}/{})",
+                    hex(err.sense_key),
+                    hex(err.asc),
+                    hex(err.ascq)
+                )
+            }
             Self::Parse(msg) => write!(f, "parse error: {msg}"),
             Self::TrackFormatMismatch {
                 track_number,
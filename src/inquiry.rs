@@ -0,0 +1,109 @@
+use std::fmt;
+use std::io;
+
+/// Allocation length for standard INQUIRY data; the identification fields
+/// end at byte 36.
+pub(crate) const INQUIRY_RESPONSE_SIZE: usize = 96;
+
+/// Drive identification returned by the SCSI INQUIRY command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InquiryData {
+    /// Peripheral device type from byte 0; `0x05` for CD/DVD devices.
+    pub peripheral_device_type: u8,
+    /// T10 vendor identification, for example `PLEXTOR` or `HL-DT-ST`.
+    pub vendor: String,
+    /// Product identification, for example `DVDR   PX-716A`.
+    pub product: String,
+    /// Product (firmware) revision level.
+    pub revision: String,
+}
+
+impl fmt::Display for InquiryData {
+    /// Formats as `vendor product revision`, for example
+    /// `PLEXTOR DVDR   PX-716A 1.11`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = [&self.vendor, &self.product, &self.revision];
+        let mut fields = fields.iter().filter(|field| !field.is_empty());
+        if let Some(first) = fields.next() {
+            f.write_str(first)?;
+        }
+        for field in fields {
+            write!(f, " {field}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Build a standard INQUIRY (0x12) command descriptor block.
+pub(crate) fn build_inquiry_cdb(allocation_length: u8) -> [u8; 6] {
+    let mut cdb = [0u8; 6];
+    cdb[0] = 0x12;
+    cdb[4] = allocation_length;
+    cdb
+}
+
+/// Parse standard INQUIRY data.
+///
+/// Vendor, product and revision are space-padded ASCII on the wire; trailing
+/// padding is removed, inner spaces are kept as the drive reported them.
+pub(crate) fn parse_inquiry(data: &[u8]) -> io::Result<InquiryData> {
+    const MINIMUM_RESPONSE_SIZE: usize = 36;
+
+    if data.len() < MINIMUM_RESPONSE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "INQUIRY response is too short",
+        ));
+    }
+
+    Ok(InquiryData {
+        peripheral_device_type: data[0] & 0x1F,
+        vendor: ascii_field(&data[8..16]),
+        product: ascii_field(&data[16..32]),
+        revision: ascii_field(&data[32..36]),
+    })
+}
+
+fn ascii_field(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_inquiry_cdb, parse_inquiry};
+
+    #[test]
+    fn builds_inquiry_cdb() {
+        assert_eq!(build_inquiry_cdb(96), [0x12, 0, 0, 0, 96, 0]);
+    }
+
+    #[test]
+    fn parses_identification_fields() {
+        let mut data = vec![0u8; 36];
+        data[0] = 0x05;
+        data[4] = 31;
+        data[8..16].copy_from_slice(b"PLEXTOR ");
+        data[16..32].copy_from_slice(b"DVDR   PX-716A  ");
+        data[32..36].copy_from_slice(b"1.11");
+
+        let inquiry = parse_inquiry(&data).unwrap();
+
+        assert_eq!(inquiry.peripheral_device_type, 0x05);
+        assert_eq!(inquiry.vendor, "PLEXTOR");
+        assert_eq!(inquiry.product, "DVDR   PX-716A");
+        assert_eq!(inquiry.revision, "1.11");
+        assert!(parse_inquiry(&data[..35]).is_err());
+        assert_eq!(inquiry.to_string(), "PLEXTOR DVDR   PX-716A 1.11");
+    }
+}
@@ -0,0 +1,241 @@
+//! Jitter correction for drives without accurate streaming.
+//!
+//! Such drives do not start every `READ CD` at the exact requested sample:
+//! each command can be off by a few samples, which skips or repeats audio
+//! where two reads meet and is heard as a click. Like cdparanoia, every read
+//! starts a little before the end of the audio already read, and that
+//! overlap is matched against the audio to find where the new read really
+//! starts.
+
+use crate::read_loop::{read_sectors_chunked, read_sectors_reported};
+use crate::{CdReaderError, ReadReport, RetryConfig, SectorReadFormat};
+
+const SECTOR_SIZE: i64 = 2352;
+const BYTES_PER_SAMPLE: i64 = 4;
+/// Sectors of already read audio each read starts in.
+const OVERLAP_SECTORS: i64 = 2;
+/// Length of the audio matched to align a read.
+const MATCH_BYTES: usize = 2352;
+/// Largest misalignment searched for: one sector.
+const MAX_JITTER_BYTES: i64 = 2352;
+
+/// End of the audio most recently returned, kept to align the read that
+/// continues it.
+#[derive(Debug, Default)]
+pub(crate) struct JitterTail {
+    /// Byte position on the disc where `data` ends.
+    end: i64,
+    data: Vec<u8>,
+}
+
+impl JitterTail {
+    /// Audio that ends right before `lba`, if enough of it is known.
+    fn before(&self, lba: u32) -> &[u8] {
+        let at = i64::from(lba) * SECTOR_SIZE;
+        let start = self.end - self.data.len() as i64;
+        if at > self.end || at - start < MATCH_BYTES as i64 {
+            return &[];
+        }
+        &self.data[..(at - start) as usize]
+    }
+
+    fn remember(&mut self, end: i64, audio: &[u8]) {
+        let keep = audio.len().min((OVERLAP_SECTORS * SECTOR_SIZE) as usize);
+        self.data = audio[audio.len() - keep..].to_vec();
+        self.end = end;
+    }
+}
+
+/// Read `sectors` audio sectors starting at `lba`, stitching overlapping
+/// reads at the position where their audio matches.
+///
+/// If `tail` holds the audio right before `lba`, the first read is aligned
+/// to it too, so consecutive calls join without clicks. A read that cannot
+/// be aligned is repeated, and the read fails once that happened
+/// `max_attempts` times in a row. With a `report`, the reads are recorded
+/// in it and unreadable sectors are zero-filled.
+pub(crate) fn read_aligned<F>(
+    lba: u32,
+    sectors: u32,
+    tail: &mut JitterTail,
+    cfg: &RetryConfig,
+    mut read_chunk: F,
+    mut report: Option<&mut ReadReport>,
+) -> Result<Vec<u8>, CdReaderError>
+where
+    F: FnMut(u32, u32) -> Result<Vec<u8>, CdReaderError>,
+{
+    if sectors == 0 {
+        return Ok(Vec::new());
+    }
+
+    let chunk = i64::from(SectorReadFormat::Audio.max_sectors_per_xfer());
+    let end_lba = i64::from(lba) + i64::from(sectors);
+    let start = i64::from(lba) * SECTOR_SIZE;
+    let end = end_lba * SECTOR_SIZE;
+    let mut read = |from: i64, to: i64| {
+        let (from, sectors, format) = (from as u32, (to - from) as u32, SectorReadFormat::Audio);
+        match report.as_deref_mut() {
+            Some(report) => {
+                read_sectors_reported(from, sectors, format, cfg, &mut read_chunk, report)
+            }
+            None => read_sectors_chunked(from, sectors, format, cfg, &mut read_chunk),
+        }
+    };
+
+    let mut audio = tail.before(lba).to_vec();
+    let mut audio_start = start - audio.len() as i64;
+    if audio.is_empty() {
+        // nothing to align the first read to
+        audio = read(i64::from(lba), (i64::from(lba) + chunk).min(end_lba))?;
+        audio_start = start;
+    }
+
+    let mut failures = 0;
+    while audio_start + (audio.len() as i64) < end {
+        let audio_end = audio_start + audio.len() as i64;
+        let from = (audio_end.div_euclid(SECTOR_SIZE) - OVERLAP_SECTORS).max(0);
+        let data = read(from, (from + chunk).min(end_lba))?;
+
+        match align(&audio, audio_end, &data, from * SECTOR_SIZE) {
+            Some(at) if at < data.len() => {
+                audio.extend_from_slice(&data[at..]);
+                failures = 0;
+            }
+            _ => {
+                failures += 1;
+                if failures >= cfg.max_attempts.max(1) {
+                    return Err(CdReaderError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("could not align overlapping reads at LBA {from}"),
+                    )));
+                }
+            }
+        }
+    }
+
+    audio.drain(..(start - audio_start) as usize);
+    audio.truncate(sectors as usize * SECTOR_SIZE as usize);
+    tail.remember(end, &audio);
+    Ok(audio)
+}
+
+/// Find where `data`, requested from disc position `data_start`, continues
+/// `audio`, which ends at disc position `audio_end`.
+///
+/// Returns the index in `data` of the first byte after `audio`. The smallest
+/// misalignment wins, so silence matches without a shift.
+fn align(audio: &[u8], audio_end: i64, data: &[u8], data_start: i64) -> Option<usize> {
+    let reference = &audio[audio.len() - audio.len().min(MATCH_BYTES)..];
+    let expected = audio_end - reference.len() as i64 - data_start;
+
+    (0..=MAX_JITTER_BYTES / BYTES_PER_SAMPLE)
+        .flat_map(|samples| [samples, -samples])
+        .map(|samples| expected - samples * BYTES_PER_SAMPLE)
+        .filter(|&at| at >= 0)
+        .map(|at| at as usize)
+        .find(|&at| data.get(at..at + reference.len()) == Some(reference))
+        .map(|at| at + reference.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{JitterTail, align, read_aligned};
+    use crate::{CdReaderError, RetryConfig};
+
+    /// Audio byte at a disc position, unique enough to never match shifted.
+    fn byte_at(position: i64) -> u8 {
+        let sample = position.div_euclid(4) as u64;
+        (sample.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 56) as u8 ^ position.rem_euclid(4) as u8
+    }
+
+    fn audio(from: i64, len: usize) -> Vec<u8> {
+        (from..from + len as i64).map(byte_at).collect()
+    }
+
+    fn no_backoff() -> RetryConfig {
+        RetryConfig::default()
+            .with_initial_backoff(Duration::ZERO)
+            .with_max_backoff(Duration::ZERO)
+    }
+
+    /// Drive that starts each read `jitter[n]` samples off.
+    fn jittery_read(jitter: &[i64]) -> impl FnMut(u32, u32) -> Result<Vec<u8>, CdReaderError> + '_ {
+        let mut reads = jitter.iter().chain(std::iter::repeat(&0));
+        move |lba, sectors| {
+            let shift = reads.next().unwrap() * 4;
+            Ok(audio(
+                i64::from(lba) * 2352 + shift,
+                sectors as usize * 2352,
+            ))
+        }
+    }
+
+    #[test]
+    fn finds_the_shift_of_a_read() {
+        let known = audio(0, 3 * 2352);
+        let late = audio(2352 + 12, 4 * 2352);
+        assert_eq!(align(&known, 3 * 2352, &late, 2352), Some(2 * 2352 - 12));
+
+        let early = audio(2352 - 40, 4 * 2352);
+        assert_eq!(align(&known, 3 * 2352, &early, 2352), Some(2 * 2352 + 40));
+
+        let unrelated = audio(100 * 2352, 4 * 2352);
+        assert_eq!(align(&known, 3 * 2352, &unrelated, 2352), None);
+    }
+
+    #[test]
+    fn stitches_jittered_reads_without_gaps_or_repeats() {
+        let jitter = [0, 3, -5, 17, -1, 0, 2];
+        let mut tail = JitterTail::default();
+
+        let data = read_aligned(
+            0,
+            100,
+            &mut tail,
+            &no_backoff(),
+            jittery_read(&jitter),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(data, audio(0, 100 * 2352));
+    }
+
+    #[test]
+    fn aligns_consecutive_calls_through_the_tail() {
+        let jitter = [0, 0, 9, -9];
+        let mut read = jittery_read(&jitter);
+        let mut tail = JitterTail::default();
+
+        let first = read_aligned(0, 27, &mut tail, &no_backoff(), &mut read, None).unwrap();
+        let second = read_aligned(27, 27, &mut tail, &no_backoff(), &mut read, None).unwrap();
+
+        assert_eq!([first, second].concat(), audio(0, 54 * 2352));
+    }
+
+    #[test]
+    fn fails_when_reads_never_line_up() {
+        let mut tail = JitterTail::default();
+        let mut reads = 0;
+
+        let result = read_aligned(
+            0,
+            60,
+            &mut tail,
+            &no_backoff().with_max_attempts(3),
+            |lba, sectors| {
+                reads += 1;
+                // every read after the first returns unrelated audio
+                let from = if reads == 1 { 0 } else { 1_000_000 };
+                Ok(audio(from + i64::from(lba) * 2352, sectors as usize * 2352))
+            },
+            None,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(reads, 4);
+    }
+}
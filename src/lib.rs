@@ -38,7 +38,23 @@
 //! ```
 //!
 //! If you already know the platform-specific device path, use
-//! [`CdReader::open_path`] instead.
+//! [`CdReader::open_path`] instead. To follow drives being connected and
+//! disconnected, such as USB drives, use a [`DriveMonitor`].
+//!
+//! A disc that was just inserted takes a few seconds to spin up, and reads
+//! fail until then. [`CdReader::wait_until_ready`] polls the drive until the
+//! disc can be read, and [`CdReader::media_state`] reports whether the tray
+//! is open or empty. To react to discs being inserted and removed, iterate
+//! over [`CdReader::media_events`].
+//!
+//! Reading does not have to go through a physical drive: anything that
+//! implements [`DriveBackend`] can be wrapped with [`CdReader::from_backend`]
+//! and used with the rest of the API. [`CueImage`] is such a backend for
+//! BIN/CUE disc images, which is handy for testing without an optical drive,
+//! and [`MockDrive`] serves generated sectors with scripted read failures.
+//! On Linux, `CdReader::open_path_with_capture` records the raw SCSI traffic
+//! of a real drive, which [`ReplayDrive`] can play back later, for example to
+//! reproduce a bug report.
 //!
 //! ## Reading ToC
 //!
@@ -97,6 +113,27 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 //!
+//! Older drives without accurate streaming click at chunk boundaries; see
+//! [`TrackStreamOptions::with_jitter_correction`] and
+//! [`ReadOptions::with_jitter_correction`].
+//!
+//! Drives conceal errors on damaged discs instead of reporting them, so a
+//! successful read can still contain wrong audio.
+//! [`CdReader::read_track_secure`] reads every sector until enough reads
+//! agree and reports a [`SectorConfidence`] for each one. Since drives cache
+//! what they read, pair it with [`ReadOptions::with_cache_defeat`]. Drives
+//! with C2 error pointers can instead flag suspect samples in a single read,
+//! see [`SectorReadFormat::AudioWithC2`] and [`C2Read`].
+//!
+//! [`CdReader::read_track_with_report`] zero-fills sectors that cannot be
+//! read instead of failing, and lists them in a [`ReadReport`] along with
+//! the sectors that needed retries. Other reads can skip them too, with
+//! [`RetryConfig::with_unreadable_sectors`].
+//!
+//! Drive failures are reported as [`CdReaderError::Scsi`];
+//! [`ScsiError::describe`] decodes their sense data, for example into
+//! "MEDIUM ERROR: L-EC uncorrectable error".
+//!
 //! ## Track format
 //!
 //! Track data is raw [PCM](https://en.wikipedia.org/wiki/Pulse-code_modulation),
@@ -130,31 +167,131 @@
 //! Audio CDs carry almost no semantic metadata. [CD-TEXT] exists but is
 //! unreliable and because of that is not provided by this lbirary. The practical approach is to
 //! calculate a Disc ID from the ToC and look it up on a service such as
-//! [MusicBrainz]. The [`Toc`] struct exposes everything required for the
-//! [MusicBrainz disc ID algorithm].
+//! [MusicBrainz]. [`Toc::musicbrainz_disc_id`] implements the
+//! [MusicBrainz disc ID algorithm], including the CD-Extra adjustments:
+//!
+//! ```no_run
+//! use cd_da_reader::CdReader;
+//!
+//! let reader = CdReader::open_default()?;
+//! let disc_id = reader.read_toc()?.musicbrainz_disc_id()?;
+//! println!("https://musicbrainz.org/cdtoc/{}", disc_id.id());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! For FreeDB-compatible services there is [`Toc::cddb_disc_id`], together
+//! with [`Toc::cddb_query`] for the matching `cddb query` command.
+//!
+//! ## Verifying rips
+//!
+//! [`Toc::accuraterip_disc_id`] gives the URL of the disc's [AccurateRip]
+//! entry. Once that response is downloaded, [`AccurateRipResponse`] can check
+//! a track against it offline:
+//!
+//! ```no_run
+//! use cd_da_reader::{AccurateRipResponse, CdReader, accuraterip_checksums};
+//!
+//! let reader = CdReader::open_default()?;
+//! let toc = reader.read_toc()?;
+//! let response = AccurateRipResponse::parse(&std::fs::read("dBAR.bin")?)?;
+//!
+//! let pcm = reader.read_track(&toc, 1)?;
+//! let checksums = accuraterip_checksums(&toc, 1, &pcm)?;
+//! if let Some(found) = response.find_match(0, &checksums) {
+//!     println!("accurately ripped, confidence {}", found.confidence);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
 //!
+//! Checksums only match other people's rips when the drive's read offset is
+//! corrected, see [`ReadOptions::with_read_offset`]. For common drive models
+//! [`CdReader::known_read_offset`] looks the offset up from the drive's
+//! INQUIRY identification:
+//!
+//! ```no_run
+//! use cd_da_reader::{CdReader, ReadOptions};
+//!
+//! let reader = CdReader::open_default()?;
+//! let offset = reader.known_read_offset()?.unwrap_or(0);
+//! let options = ReadOptions::default().with_read_offset(offset);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The [CUETools Database] works as a second opinion: [`Toc::ctdb_toc_id`]
+//! identifies the disc, [`CtdbHasher`] computes the CRC32 over all of its
+//! audio tracks, and [`CtdbResponse`] checks it against a lookup response.
+//!
+//! [AccurateRip]: http://www.accuraterip.com/
+//! [CUETools Database]: http://db.cuetools.net/
 //! [CD-TEXT]: https://en.wikipedia.org/wiki/CD-Text
 //! [MusicBrainz]: https://musicbrainz.org/
 //! [MusicBrainz disc ID algorithm]: https://musicbrainz.org/doc/Disc_ID_Calculation
 mod platform;
 
+mod accuraterip;
+mod backend;
+mod c2;
+mod cache;
+mod capabilities;
+mod capture;
+mod ctdb;
+mod cue_image;
 mod data_reader;
+mod disc_id;
 mod discovery;
+mod drive_monitor;
+mod drive_offsets;
 mod errors;
+mod inquiry;
+mod jitter;
+mod media;
+mod media_events;
+mod mock;
 mod read_loop;
+mod read_offset;
+mod read_report;
 mod retry;
+mod secure_read;
+mod sense;
+mod speed;
 mod stream;
+#[cfg(test)]
+mod test_support;
+mod tray;
 mod utils;
-pub use data_reader::{ReadOptions, SectorReadFormat};
+pub use accuraterip::{
+    AccurateRipChecksums, AccurateRipDiscId, AccurateRipHasher, AccurateRipMatch,
+    AccurateRipPressing, AccurateRipResponse, AccurateRipTrackEntry, AccurateRipVersion,
+    accuraterip_checksums,
+};
+pub use backend::DriveBackend;
+pub use c2::C2Read;
+pub use cache::CacheDefeat;
+pub use capabilities::DriveCapabilities;
+pub use capture::ReplayDrive;
+pub use ctdb::{CtdbEntry, CtdbHasher, CtdbResponse, CtdbVerdict};
+pub use cue_image::CueImage;
+pub use data_reader::{ReadOptions, SectorReadFormat, TrackInformation};
+pub use disc_id::MusicBrainzDiscId;
 pub use discovery::DriveInfo;
+pub use drive_monitor::{DriveEvent, DriveMonitor};
+pub use drive_offsets::lookup_drive_offset;
 pub use errors::{CdReaderError, ScsiError, ScsiOp};
-pub use retry::RetryConfig;
+pub use inquiry::InquiryData;
+pub use media::MediaState;
+pub use media_events::{MediaEvent, MediaEventStatus, MediaEvents};
+pub use mock::{MockCommand, MockDrive, MockFault};
+pub use read_report::{ReadReport, RetriedSector};
+pub use retry::{RetryAction, RetryConfig, UnreadableSectors};
+pub use secure_read::{SectorConfidence, SecureRead, SecureReadConfig};
+pub use sense::{SenseCategory, SenseDescription};
+pub use speed::ReadSpeed;
 pub use stream::{TrackStream, TrackStreamOptions};
 
 mod parse_toc;
 
 /// Representation of the track from TOC, purely in terms of data location on the CD.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Track {
     /// Track number from the Table of Contents (read from the CD itself).
     /// It usually starts with 1, but you should read this value directly when
@@ -170,7 +307,7 @@ pub struct Track {
 
 /// Table of Contents, read directly from the Audio CD. The most important part
 /// is the `tracks` vector, which allows you to read raw track data.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Toc {
     /// Helper value with the first track number
     pub first_track: u8,
@@ -188,7 +325,7 @@ pub struct Toc {
 /// handle to the open CD drive to read from it and it is correctly closed when CDReader
 /// is dropped.
 pub struct CdReader {
-    drive: platform::Drive,
+    drive: Box<dyn DriveBackend>,
 }
 
 impl CdReader {
@@ -204,18 +341,43 @@ impl CdReader {
     /// Example paths are `/dev/sr0` on Linux, `disk6` on macOS, and
     /// `\\.\E:` on Windows. The reader owns the opened drive until it is dropped.
     pub fn open_path(path: &str) -> Result<Self, CdReaderError> {
-        Ok(Self {
-            drive: platform::Drive::open(path)?,
-        })
+        Ok(Self::from_backend(platform::Drive::open(path)?))
     }
 
-    #[cfg(test)]
-    pub(crate) fn test_reader() -> Self {
+    /// Opens a CD drive like [`CdReader::open_path`] and records every SCSI
+    /// command issued to it, along with the returned data, status and sense
+    /// bytes, into a capture file at `capture_path`.
+    ///
+    /// Capture files are meant to be attached to bug reports: replaying one
+    /// through [`ReplayDrive`] reproduces the drive's exact responses on any
+    /// platform. If writing the capture fails, for example because the disk
+    /// is full, capturing stops and the drive keeps working.
+    #[cfg(target_os = "linux")]
+    pub fn open_path_with_capture(
+        path: &str,
+        capture_path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, CdReaderError> {
+        Ok(Self::from_backend(platform::Drive::open_with_capture(
+            path,
+            capture_path.as_ref(),
+        )?))
+    }
+
+    /// Creates a reader on top of a custom [`DriveBackend`].
+    ///
+    /// All reading APIs, including retries, streaming and track format
+    /// detection, issue their commands through the provided backend.
+    pub fn from_backend<B: DriveBackend + 'static>(backend: B) -> Self {
         Self {
-            drive: platform::Drive::test_drive(),
+            drive: Box::new(backend),
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn test_reader() -> Self {
+        Self::from_backend(MockDrive::audio_cd(&[1_000]))
+    }
+
     /// While this is a low-level library and does not include any codecs to compress the audio,
     /// it includes a helper function to convert raw PCM data into a wav file, which is done by
     /// prepending a 44 RIFF bytes header
@@ -238,6 +400,93 @@ impl CdReader {
         self.drive.read_toc()
     }
 
+    /// Identify the drive with the SCSI INQUIRY command.
+    ///
+    /// On macOS the identification comes from the IORegistry, which the
+    /// system fills from the drive's own INQUIRY response.
+    pub fn inquiry(&self) -> Result<InquiryData, CdReaderError> {
+        self.drive.inquiry()
+    }
+
+    /// Read offset of the opened drive from the built-in table, in samples.
+    ///
+    /// Returns `None` for drive models that are not in the table; their
+    /// offset has to be measured with a key disc instead.
+    pub fn known_read_offset(&self) -> Result<Option<i32>, CdReaderError> {
+        Ok(self.inquiry()?.known_read_offset())
+    }
+
+    /// Read what the drive reports it supports: CD-DA commands, accurate
+    /// streaming, C2 error pointers, subchannel and CD-TEXT reading, and its
+    /// speeds.
+    ///
+    /// Not available on macOS, where the system does not pass MODE SENSE
+    /// through to the drive; the error has kind
+    /// [`std::io::ErrorKind::Unsupported`] there. MMC has no capability bit
+    /// for reading into the lead-out, use [`CdReader::can_read_lead_out`] to
+    /// probe it.
+    pub fn capabilities(&self) -> Result<DriveCapabilities, CdReaderError> {
+        self.drive.capabilities()
+    }
+
+    /// Probe whether the drive returns audio sectors from the lead-out, by
+    /// reading the first lead-out sector once.
+    ///
+    /// Drives that can do this allow positive read offsets to be corrected
+    /// without zero-filling the end of the last track. A SCSI error means it
+    /// cannot; transport errors are returned as they are.
+    pub fn can_read_lead_out(&self, toc: &Toc) -> Result<bool, CdReaderError> {
+        match self
+            .drive
+            .read_cd_chunk(toc.leadout_lba, 1, SectorReadFormat::Audio)
+        {
+            Ok(data) => Ok(data.len() == SectorReadFormat::Audio.sector_size()),
+            Err(CdReaderError::Scsi(_)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Open the tray, or eject the disc from a slot-loading drive.
+    ///
+    /// Fails with a SCSI error while the tray is locked with
+    /// [`CdReader::lock_tray`]. On macOS the disc's volume has to be
+    /// unmounted first.
+    pub fn eject(&self) -> Result<(), CdReaderError> {
+        self.drive.eject()
+    }
+
+    /// Close the tray. Slot-loading drives accept this without doing
+    /// anything. Not available on macOS.
+    pub fn load(&self) -> Result<(), CdReaderError> {
+        self.drive.load()
+    }
+
+    /// Prevent the disc from being removed, including with the drive's eject
+    /// button, until [`CdReader::unlock_tray`] is called. Not available on
+    /// macOS.
+    ///
+    /// Depending on the platform the lock can outlive this reader, so unlock
+    /// the tray before dropping it.
+    pub fn lock_tray(&self) -> Result<(), CdReaderError> {
+        self.drive.set_tray_locked(true)
+    }
+
+    /// Allow the disc to be removed again after [`CdReader::lock_tray`].
+    pub fn unlock_tray(&self) -> Result<(), CdReaderError> {
+        self.drive.set_tray_locked(false)
+    }
+
+    /// Set the speed the drive reads at.
+    ///
+    /// Slower speeds give the drive more time per sector, which dramatically
+    /// reduces read errors on scratched discs, and make it quieter. The drive
+    /// rounds the speed to one it supports and keeps it until it is changed
+    /// again or the disc is ejected. Both SET CD SPEED and SET STREAMING are
+    /// issued, and the call succeeds if the drive accepts either of them.
+    pub fn set_read_speed(&self, speed: ReadSpeed) -> Result<(), CdReaderError> {
+        self.drive.set_read_speed(speed)
+    }
+
     /// Read an audio track using the default options.
     ///
     /// It returns raw PCM data, but if you want to save it directly and make it playable,
@@ -257,9 +506,12 @@ impl CdReader {
             data_reader::validate_track_format(track, options.format())?;
         }
 
-        let (start_lba, sectors) =
-            utils::get_track_bounds(toc, track_no).map_err(CdReaderError::Io)?;
-        self.read_sector_range(start_lba, sectors, options)
+        let (start_lba, sectors, audio_end_lba) =
+            utils::get_track_read_bounds(toc, track_no).map_err(CdReaderError::Io)?;
+        if let Some(speed) = options.read_speed() {
+            self.set_read_speed(speed)?;
+        }
+        self.read_sectors(start_lba, sectors, options, Some(audio_end_lba))
     }
 
     /// Read an arbitrary range of sectors using explicit format and retry options.
@@ -269,19 +521,97 @@ impl CdReader {
     /// Callers are responsible for providing valid sector boundaries and selecting
     /// a format compatible with the sectors on the disc. Prefer [`CdReader::read_track`]
     /// or [`CdReader::read_track_with_options`] when reading a complete TOC track.
+    ///
+    /// With a read offset set, samples before LBA 0 are returned as silence,
+    /// but since the end of the audio session is not known here, a failed read
+    /// past the lead-out is reported as an error.
     pub fn read_sector_range(
         &self,
         start_lba: u32,
         sectors: u32,
         options: &ReadOptions,
+    ) -> Result<Vec<u8>, CdReaderError> {
+        if let Some(speed) = options.read_speed() {
+            self.set_read_speed(speed)?;
+        }
+        self.read_sectors(start_lba, sectors, options, None)
+    }
+
+    /// Read a sector range, applying the read offset from `options`.
+    ///
+    /// `audio_end_lba` is the end of the audio session; shifted reads past it
+    /// are attempted once and zero-filled if the drive cannot overread.
+    pub(crate) fn read_sectors(
+        &self,
+        start_lba: u32,
+        sectors: u32,
+        options: &ReadOptions,
+        audio_end_lba: Option<u32>,
+    ) -> Result<Vec<u8>, CdReaderError> {
+        let mut tail = jitter::JitterTail::default();
+        self.read_sectors_continuing(start_lba, sectors, options, audio_end_lba, &mut tail, None)
+    }
+
+    /// Like `read_sectors`, but with jitter correction the reads are aligned
+    /// to `tail`, the end of the previous read, so that consecutive calls
+    /// join seamlessly.
+    ///
+    /// With a `report`, unreadable sectors are zero-filled and recorded in it
+    /// instead of failing the read, except for overreads past the audio
+    /// session.
+    pub(crate) fn read_sectors_continuing(
+        &self,
+        start_lba: u32,
+        sectors: u32,
+        options: &ReadOptions,
+        audio_end_lba: Option<u32>,
+        tail: &mut jitter::JitterTail,
+        mut report: Option<&mut ReadReport>,
     ) -> Result<Vec<u8>, CdReaderError> {
         let format = options.format();
-        read_loop::read_sectors_chunked(
+        if options.jitter_correction() && format != SectorReadFormat::Audio {
+            return Err(CdReaderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "jitter correction is only supported for audio sectors without C2",
+            )));
+        }
+        let mut read = |lba: u32, sectors: u32, retry: &RetryConfig, reported: bool| {
+            let read_chunk =
+                |lba, chunk_sectors| self.drive.read_cd_chunk(lba, chunk_sectors, format);
+            let report = report.as_deref_mut().filter(|_| reported);
+            if options.jitter_correction() {
+                jitter::read_aligned(lba, sectors, tail, retry, read_chunk, report)
+            } else if let Some(report) = report {
+                read_loop::read_sectors_reported(lba, sectors, format, retry, read_chunk, report)
+            } else {
+                read_loop::read_sectors_chunked(lba, sectors, format, retry, read_chunk)
+            }
+        };
+
+        if options.read_offset() == 0 {
+            return read(start_lba, sectors, options.retry(), true);
+        }
+        if format != SectorReadFormat::Audio {
+            return Err(CdReaderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "read offset correction is only supported for audio sectors without C2",
+            )));
+        }
+
+        let overread_retry = options.retry().clone().with_max_attempts(1);
+        read_offset::read_with_offset(
             start_lba,
             sectors,
-            format,
-            options.retry(),
-            |lba, chunk_sectors| self.drive.read_cd_chunk(lba, chunk_sectors, format),
+            options.read_offset(),
+            audio_end_lba,
+            |lba, sectors, past_end| {
+                let retry = if past_end {
+                    &overread_retry
+                } else {
+                    options.retry()
+                };
+                read(lba, sectors, retry, !past_end)
+            },
         )
     }
 }
@@ -0,0 +1,160 @@
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::{CdReader, CdReaderError};
+
+const INITIAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether the drive has a disc it can read, from TEST UNIT READY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaState {
+    /// A disc is loaded and can be read.
+    Ready,
+    /// A disc is loaded but still spinning up or being identified.
+    BecomingReady,
+    /// The tray is closed and empty.
+    NoMedium,
+    /// The tray is open.
+    TrayOpen,
+}
+
+/// Build a TEST UNIT READY (0x00) command descriptor block.
+pub(crate) fn build_test_unit_ready_cdb() -> [u8; 6] {
+    [0u8; 6]
+}
+
+/// Decode the outcome of TEST UNIT READY.
+///
+/// Failures that do not describe the medium, such as transport errors, are
+/// returned unchanged.
+pub(crate) fn media_state_from_test_unit_ready(
+    result: Result<(), CdReaderError>,
+) -> Result<MediaState, CdReaderError> {
+    let error = match result {
+        Ok(()) => return Ok(MediaState::Ready),
+        Err(CdReaderError::Scsi(error)) => error,
+        Err(error) => return Err(error),
+    };
+
+    match (error.sense_key, error.asc, error.ascq) {
+        // NOT READY, MEDIUM NOT PRESENT - TRAY OPEN
+        (Some(0x02), Some(0x3A), Some(0x02)) => Ok(MediaState::TrayOpen),
+        // NOT READY, MEDIUM NOT PRESENT (tray closed or unknown)
+        (Some(0x02), Some(0x3A), _) => Ok(MediaState::NoMedium),
+        // NOT READY, LOGICAL UNIT NOT READY (becoming ready, in progress, ...)
+        (Some(0x02), Some(0x04), _) => Ok(MediaState::BecomingReady),
+        // UNIT ATTENTION, NOT READY TO READY CHANGE, MEDIUM MAY HAVE CHANGED;
+        // reported once after a disc is inserted
+        (Some(0x06), Some(0x28), _) => Ok(MediaState::BecomingReady),
+        _ => Err(CdReaderError::Scsi(error)),
+    }
+}
+
+impl CdReader {
+    /// Check whether the drive has a readable disc, with TEST UNIT READY.
+    ///
+    /// On macOS the state is derived from whether the disc's device can be
+    /// queried, so only [`MediaState::Ready`] and [`MediaState::NoMedium`]
+    /// are reported there.
+    pub fn media_state(&self) -> Result<MediaState, CdReaderError> {
+        self.drive.media_state()
+    }
+
+    /// Poll [`CdReader::media_state`] until the disc is ready.
+    ///
+    /// Polls start 100 ms apart and back off to one per second. Fails with
+    /// [`std::io::ErrorKind::TimedOut`] if the disc is not ready within
+    /// `timeout`, for example because the tray stays empty.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use cd_da_reader::CdReader;
+    ///
+    /// let reader = CdReader::open_path("/dev/sr0")?;
+    /// reader.wait_until_ready(Duration::from_secs(30))?;
+    /// let toc = reader.read_toc()?;
+    /// # Ok::<(), cd_da_reader::CdReaderError>(())
+    /// ```
+    pub fn wait_until_ready(&self, timeout: Duration) -> Result<(), CdReaderError> {
+        let deadline = Instant::now() + timeout;
+        let mut interval = INITIAL_POLL_INTERVAL;
+
+        loop {
+            let state = self.media_state()?;
+            if state == MediaState::Ready {
+                return Ok(());
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(CdReaderError::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("drive was not ready within {timeout:?} (last state: {state:?})"),
+                )));
+            }
+
+            sleep(interval.min(remaining));
+            interval = interval.saturating_mul(2).min(MAX_POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{MediaState, media_state_from_test_unit_ready};
+    use crate::{CdReader, CdReaderError, MockDrive, ScsiError, ScsiOp};
+
+    fn not_ready(sense: (u8, u8, u8)) -> Result<(), CdReaderError> {
+        Err(CdReaderError::Scsi(ScsiError::check_condition(
+            ScsiOp::TestUnitReady,
+            None,
+            None,
+            sense,
+        )))
+    }
+
+    #[test]
+    fn decodes_test_unit_ready_sense_data() {
+        let decode = |result| media_state_from_test_unit_ready(result).unwrap();
+
+        assert_eq!(decode(Ok(())), MediaState::Ready);
+        assert_eq!(decode(not_ready((0x02, 0x3A, 0x02))), MediaState::TrayOpen);
+        assert_eq!(decode(not_ready((0x02, 0x3A, 0x01))), MediaState::NoMedium);
+        assert_eq!(decode(not_ready((0x02, 0x3A, 0x00))), MediaState::NoMedium);
+        assert_eq!(
+            decode(not_ready((0x02, 0x04, 0x01))),
+            MediaState::BecomingReady
+        );
+        assert_eq!(
+            decode(not_ready((0x06, 0x28, 0x00))),
+            MediaState::BecomingReady
+        );
+        assert!(media_state_from_test_unit_ready(not_ready((0x03, 0x11, 0x05))).is_err());
+    }
+
+    #[test]
+    fn waits_until_the_disc_spins_up() {
+        let drive = MockDrive::audio_cd(&[10]);
+        drive.script_media_states([MediaState::TrayOpen, MediaState::BecomingReady]);
+        let reader = CdReader::from_backend(drive);
+
+        reader.wait_until_ready(Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn times_out_while_the_tray_stays_empty() {
+        let drive = MockDrive::audio_cd(&[10]);
+        drive.script_media_states([MediaState::NoMedium; 10]);
+        let reader = CdReader::from_backend(drive);
+
+        match reader.wait_until_ready(Duration::from_millis(150)) {
+            Err(CdReaderError::Io(error)) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::TimedOut)
+            }
+            other => panic!("expected TimedOut, got {other:?}"),
+        }
+    }
+}
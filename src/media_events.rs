@@ -0,0 +1,233 @@
+use std::io;
+use std::thread::sleep;
+use std::time::Duration;
+
+use crate::{CdReader, CdReaderError};
+
+/// Allocation length for a media class event: the 4-byte header and one
+/// 4-byte event descriptor.
+pub(crate) const EVENT_STATUS_RESPONSE_SIZE: usize = 8;
+
+const MEDIA_CLASS: u8 = 4;
+
+/// Disc change reported by the drive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaEvent {
+    /// A disc was inserted and the tray closed.
+    Inserted,
+    /// The disc was removed.
+    Removed,
+    /// The eject button was pressed. Unless the tray is locked, the drive
+    /// ejects the disc on its own and [`MediaEvent::Removed`] follows.
+    EjectRequested,
+}
+
+/// Result of one GET EVENT STATUS NOTIFICATION poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaEventStatus {
+    /// Event reported since the previous poll, if any. The drive reports
+    /// each event once.
+    pub event: Option<MediaEvent>,
+    /// Whether the tray is open.
+    pub tray_open: bool,
+    /// Whether a disc is present.
+    pub media_present: bool,
+}
+
+/// Build a polled GET EVENT STATUS NOTIFICATION (0x4A) command descriptor
+/// block for the media event class.
+pub(crate) fn build_get_event_status_cdb(allocation_length: u16) -> [u8; 10] {
+    let mut cdb = [0u8; 10];
+    cdb[0] = 0x4A;
+    cdb[1] = 0x01; // polled
+    cdb[4] = 1 << MEDIA_CLASS;
+    cdb[7..9].copy_from_slice(&allocation_length.to_be_bytes());
+    cdb
+}
+
+/// Parse a media class GET EVENT STATUS NOTIFICATION response.
+pub(crate) fn parse_media_event_status(data: &[u8]) -> io::Result<MediaEventStatus> {
+    if data.len() < EVENT_STATUS_RESPONSE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "event status response is too short",
+        ));
+    }
+    // NEA: the drive has no media class event to report
+    if data[2] & 0x80 != 0 || data[2] & 0x07 != MEDIA_CLASS {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "drive does not report media events",
+        ));
+    }
+
+    let event = match data[4] & 0x0F {
+        0x01 => Some(MediaEvent::EjectRequested),
+        // new media, or media changed
+        0x02 | 0x04 => Some(MediaEvent::Inserted),
+        0x03 => Some(MediaEvent::Removed),
+        _ => None,
+    };
+
+    Ok(MediaEventStatus {
+        event,
+        tray_open: data[5] & 0x01 != 0,
+        media_present: data[5] & 0x02 != 0,
+    })
+}
+
+/// Blocking iterator over disc changes, created with
+/// [`CdReader::media_events`].
+///
+/// It never ends on its own; errors are yielded and polling continues on
+/// the next call, so stop iterating to give up.
+pub struct MediaEvents<'a> {
+    reader: &'a CdReader,
+    interval: Duration,
+    media_present: Option<bool>,
+}
+
+impl Iterator for MediaEvents<'_> {
+    type Item = Result<MediaEvent, CdReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let status = match self.reader.poll_media_event() {
+                Ok(status) => status,
+                Err(error) => return Some(Err(error)),
+            };
+
+            // the OS may poll the drive too and consume its events, so
+            // presence changes count as events as well
+            let event = status
+                .event
+                .or(match (self.media_present, status.media_present) {
+                    (Some(false), true) => Some(MediaEvent::Inserted),
+                    (Some(true), false) => Some(MediaEvent::Removed),
+                    _ => None,
+                });
+            self.media_present = Some(status.media_present);
+
+            if let Some(event) = event {
+                return Some(Ok(event));
+            }
+            sleep(self.interval);
+        }
+    }
+}
+
+impl CdReader {
+    /// Poll the drive once for a media event with GET EVENT STATUS
+    /// NOTIFICATION. Not available on macOS.
+    pub fn poll_media_event(&self) -> Result<MediaEventStatus, CdReaderError> {
+        self.drive.poll_media_event()
+    }
+
+    /// Watch for discs being inserted and removed, polling the drive every
+    /// `interval`.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use cd_da_reader::{CdReader, MediaEvent};
+    ///
+    /// let reader = CdReader::open_path("/dev/sr0")?;
+    /// for event in reader.media_events(Duration::from_millis(500)) {
+    ///     match event? {
+    ///         MediaEvent::Inserted => println!("{:?}", reader.read_toc()?),
+    ///         MediaEvent::Removed => println!("disc removed"),
+    ///         MediaEvent::EjectRequested => {}
+    ///     }
+    /// }
+    /// # Ok::<(), cd_da_reader::CdReaderError>(())
+    /// ```
+    ///
+    /// A freshly inserted disc may still be spinning up when
+    /// [`MediaEvent::Inserted`] is reported, see
+    /// [`CdReader::wait_until_ready`].
+    pub fn media_events(&self, interval: Duration) -> MediaEvents<'_> {
+        MediaEvents {
+            reader: self,
+            interval,
+            media_present: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::MockDrive;
+
+    fn response(event_code: u8, media_status: u8) -> Vec<u8> {
+        let mut data = vec![0u8; EVENT_STATUS_RESPONSE_SIZE];
+        data[1] = 6;
+        data[2] = MEDIA_CLASS;
+        data[3] = 1 << MEDIA_CLASS;
+        data[4] = event_code;
+        data[5] = media_status;
+        data
+    }
+
+    #[test]
+    fn builds_polled_media_class_request() {
+        assert_eq!(
+            build_get_event_status_cdb(8),
+            [0x4A, 0x01, 0, 0, 0x10, 0, 0, 0x00, 0x08, 0]
+        );
+    }
+
+    #[test]
+    fn parses_media_events() {
+        let status = parse_media_event_status(&response(0x02, 0x02)).unwrap();
+        assert_eq!(
+            status,
+            MediaEventStatus {
+                event: Some(MediaEvent::Inserted),
+                tray_open: false,
+                media_present: true,
+            }
+        );
+
+        let status = parse_media_event_status(&response(0x03, 0x01)).unwrap();
+        assert_eq!(status.event, Some(MediaEvent::Removed));
+        assert!(status.tray_open);
+        assert!(!status.media_present);
+
+        let status = parse_media_event_status(&response(0x00, 0x02)).unwrap();
+        assert_eq!(status.event, None);
+
+        let mut no_event_available = response(0x00, 0x00);
+        no_event_available[2] = 0x80;
+        assert!(parse_media_event_status(&no_event_available).is_err());
+        assert!(parse_media_event_status(&response(0, 0)[..4]).is_err());
+    }
+
+    #[test]
+    fn iterator_reports_events_and_presence_changes() {
+        let drive = MockDrive::audio_cd(&[10]);
+        let status = |event, media_present| MediaEventStatus {
+            event,
+            tray_open: false,
+            media_present,
+        };
+        drive.script_media_events([
+            status(None, false),
+            status(None, false),
+            status(Some(MediaEvent::Inserted), true),
+            status(None, true),
+            // removal event consumed by someone else
+            status(None, false),
+        ]);
+        let reader = CdReader::from_backend(drive);
+
+        let events: Vec<_> = reader
+            .media_events(Duration::ZERO)
+            .take(2)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(events, vec![MediaEvent::Inserted, MediaEvent::Removed]);
+    }
+}
@@ -0,0 +1,631 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use crate::backend::unsupported;
+use crate::parse_toc::lba_to_msf;
+use crate::{
+    CdReaderError, DriveBackend, DriveCapabilities, InquiryData, MediaEventStatus, MediaState,
+    ReadSpeed, ScsiError, ScsiOp, SectorReadFormat, Toc, Track, TrackInformation,
+};
+
+/// Scripted outcome of a single `READ CD` attempt on a [`MockDrive`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockFault {
+    /// Fail with CHECK CONDITION and the given sense key, ASC and ASCQ.
+    Scsi { sense_key: u8, asc: u8, ascq: u8 },
+    /// Fail with a transport I/O error of the given kind.
+    Io(std::io::ErrorKind),
+    /// Complete the command, but return only the given number of bytes.
+    ShortRead(usize),
+    /// Complete the command, but return wrong data for the scripted sector,
+    /// like a drive concealing an uncorrectable error. C2 reads flag every
+    /// byte of the sector.
+    Corrupt,
+}
+
+/// `READ CD` command received by a [`MockDrive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockCommand {
+    /// Starting LBA of the command.
+    pub lba: u32,
+    /// Number of sectors requested.
+    pub sectors: u32,
+    /// Sector format requested.
+    pub format: SectorReadFormat,
+    /// When the command was issued, useful to assert retry backoff.
+    pub issued_at: Instant,
+}
+
+/// In-memory drive with scripted read failures, for testing code built on
+/// [`CdReader`](crate::CdReader) without hardware.
+///
+/// Every sector holds deterministic content (see [`MockDrive::sector_data`]).
+/// Faults are scripted per LBA: each `READ CD` command covering a scripted LBA
+/// consumes the next fault for it, and once the script is exhausted the reads
+/// succeed. All received commands are recorded.
+///
+/// The drive is a cheap handle to shared state, so keep a clone to inspect it
+/// after handing it to [`CdReader::from_backend`](crate::CdReader::from_backend):
+///
+/// ```
+/// use cd_da_reader::{CdReader, MockDrive, MockFault};
+///
+/// let drive = MockDrive::audio_cd(&[100]);
+/// drive.fail_lba(
+///     30,
+///     [MockFault::Scsi { sense_key: 0x03, asc: 0x11, ascq: 0x05 }],
+/// );
+///
+/// let reader = CdReader::from_backend(drive.clone());
+/// let toc = reader.read_toc()?;
+/// let data = reader.read_track(&toc, 1)?;
+///
+/// assert_eq!(data.len(), 100 * 2352);
+/// assert!(drive.commands().len() > 4);
+/// # Ok::<(), cd_da_reader::CdReaderError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockDrive {
+    state: Arc<MockState>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    toc: Toc,
+    faults: Mutex<BTreeMap<u32, VecDeque<MockFault>>>,
+    commands: Mutex<Vec<MockCommand>>,
+    inquiry: Mutex<Option<InquiryData>>,
+    capabilities: Mutex<Option<DriveCapabilities>>,
+    tray: Mutex<MockTray>,
+    read_speed: Mutex<Option<ReadSpeed>>,
+    media_states: Mutex<VecDeque<MediaState>>,
+    media_events: Mutex<VecDeque<MediaEventStatus>>,
+    cache: Mutex<MockCache>,
+    jitter: Mutex<VecDeque<i32>>,
+}
+
+#[derive(Debug, Default)]
+struct MockTray {
+    open: bool,
+    locked: bool,
+}
+
+/// Most recently read sectors, oldest first.
+#[derive(Debug, Default)]
+struct MockCache {
+    capacity: usize,
+    sectors: VecDeque<(u32, SectorReadFormat, Vec<u8>)>,
+}
+
+impl MockCache {
+    fn get(&self, lba: u32, sectors: u32, format: SectorReadFormat) -> Option<Vec<u8>> {
+        let mut data = Vec::with_capacity(sectors as usize * format.sector_size());
+        for sector_lba in lba..lba + sectors {
+            let (_, _, sector) = self.sectors.iter().find(|(cached, cached_format, _)| {
+                *cached == sector_lba && *cached_format == format
+            })?;
+            data.extend_from_slice(sector);
+        }
+        Some(data)
+    }
+
+    fn insert(&mut self, lba: u32, format: SectorReadFormat, data: &[u8]) {
+        if self.capacity == 0 {
+            return;
+        }
+        for (sector_lba, sector) in (lba..).zip(data.chunks_exact(format.sector_size())) {
+            self.sectors.retain(|(cached, _, _)| *cached != sector_lba);
+            self.sectors
+                .push_back((sector_lba, format, sector.to_vec()));
+        }
+        while self.sectors.len() > self.capacity {
+            self.sectors.pop_front();
+        }
+    }
+}
+
+impl MockDrive {
+    /// Create a drive serving the given TOC.
+    pub fn new(toc: Toc) -> Self {
+        Self {
+            state: Arc::new(MockState {
+                toc,
+                faults: Mutex::new(BTreeMap::new()),
+                commands: Mutex::new(Vec::new()),
+                inquiry: Mutex::new(None),
+                capabilities: Mutex::new(None),
+                tray: Mutex::new(MockTray::default()),
+                read_speed: Mutex::new(None),
+                media_states: Mutex::new(VecDeque::new()),
+                media_events: Mutex::new(VecDeque::new()),
+                cache: Mutex::new(MockCache::default()),
+                jitter: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    /// Create a drive with consecutive audio tracks of the given lengths in sectors.
+    pub fn audio_cd(track_sectors: &[u32]) -> Self {
+        let mut tracks = Vec::with_capacity(track_sectors.len());
+        let mut lba = 0;
+        for (idx, sectors) in track_sectors.iter().enumerate() {
+            tracks.push(Track {
+                number: idx as u8 + 1,
+                start_lba: lba,
+                start_msf: lba_to_msf(lba),
+                is_audio: true,
+            });
+            lba += sectors;
+        }
+
+        Self::new(Toc {
+            first_track: 1,
+            last_track: track_sectors.len() as u8,
+            tracks,
+            leadout_lba: lba,
+        })
+    }
+
+    /// Script the outcomes of the next reads covering `lba`, in order.
+    ///
+    /// Faults are appended to any faults already scripted for that LBA.
+    pub fn fail_lba(&self, lba: u32, faults: impl IntoIterator<Item = MockFault>) {
+        lock(&self.state.faults)
+            .entry(lba)
+            .or_default()
+            .extend(faults);
+    }
+
+    /// Make the drive answer INQUIRY with the given identification.
+    ///
+    /// Without it, INQUIRY reports the command as unsupported.
+    pub fn set_inquiry(&self, inquiry: InquiryData) {
+        *lock(&self.state.inquiry) = Some(inquiry);
+    }
+
+    /// Make the drive report the given capabilities.
+    ///
+    /// Without it, MODE SENSE reports the command as unsupported.
+    pub fn set_capabilities(&self, capabilities: DriveCapabilities) {
+        *lock(&self.state.capabilities) = Some(capabilities);
+    }
+
+    /// Whether the tray was opened with an eject and not loaded again.
+    pub fn is_tray_open(&self) -> bool {
+        lock(&self.state.tray).open
+    }
+
+    /// Whether medium removal is currently prevented.
+    pub fn is_tray_locked(&self) -> bool {
+        lock(&self.state.tray).locked
+    }
+
+    /// Script the media states reported by the next TEST UNIT READY
+    /// commands, in order. Once the script is exhausted the drive is ready.
+    pub fn script_media_states(&self, states: impl IntoIterator<Item = MediaState>) {
+        lock(&self.state.media_states).extend(states);
+    }
+
+    /// Script the results of the next GET EVENT STATUS NOTIFICATION polls,
+    /// in order. Once the script is exhausted the drive reports a loaded
+    /// disc without events.
+    pub fn script_media_events(&self, events: impl IntoIterator<Item = MediaEventStatus>) {
+        lock(&self.state.media_events).extend(events);
+    }
+
+    /// The last read speed set on the drive, if any.
+    pub fn read_speed(&self) -> Option<ReadSpeed> {
+        *lock(&self.state.read_speed)
+    }
+
+    /// All `READ CD` commands received so far, in order.
+    pub fn commands(&self) -> Vec<MockCommand> {
+        lock(&self.state.commands).clone()
+    }
+
+    /// Make the drive cache the last `sectors` sectors it read, like real
+    /// drives do.
+    ///
+    /// Reads of cached sectors return the cached data, even if it was
+    /// corrupted, without consuming scripted faults. FUA reads drop the
+    /// cache.
+    pub fn set_read_cache(&self, sectors: usize) {
+        let mut cache = lock(&self.state.cache);
+        cache.capacity = sectors;
+        cache.sectors.clear();
+    }
+
+    /// Start the next audio reads the given numbers of samples off, one
+    /// value per `READ CD` command, like a drive without accurate streaming.
+    pub fn script_jitter(&self, samples: impl IntoIterator<Item = i32>) {
+        lock(&self.state.jitter).extend(samples);
+    }
+
+    /// Content the drive returns for a sector in the given format.
+    ///
+    /// [`SectorReadFormat::AudioWithC2`] returns the audio content followed
+    /// by error pointers without errors.
+    pub fn sector_data(lba: u32, format: SectorReadFormat) -> Vec<u8> {
+        if format == SectorReadFormat::AudioWithC2 {
+            let mut data = Self::sector_data(lba, SectorReadFormat::Audio);
+            data.resize(format.sector_size(), 0);
+            return data;
+        }
+
+        (0..format.sector_size())
+            .map(|idx| pattern_byte(lba, idx))
+            .collect()
+    }
+
+    /// Pop the next fault scripted for the lowest LBA in the range, if any,
+    /// along with that LBA.
+    fn take_fault(&self, lba: u32, sectors: u32) -> Option<(u32, MockFault)> {
+        let mut faults = lock(&self.state.faults);
+        let end = lba.saturating_add(sectors);
+        let (&fault_lba, script) = faults
+            .range_mut(lba..end)
+            .find(|(_, script)| !script.is_empty())?;
+        Some((fault_lba, script.pop_front()?))
+    }
+}
+
+impl DriveBackend for MockDrive {
+    fn read_toc(&self) -> Result<Toc, CdReaderError> {
+        Ok(self.state.toc.clone())
+    }
+
+    fn read_track_information(&self, track_number: u8) -> Result<TrackInformation, CdReaderError> {
+        let toc = &self.state.toc;
+        let idx = toc
+            .tracks
+            .iter()
+            .position(|track| track.number == track_number)
+            .ok_or_else(|| {
+                CdReaderError::Scsi(ScsiError::check_condition(
+                    ScsiOp::ReadTrackInformation,
+                    None,
+                    None,
+                    (0x05, 0x24, 0x00),
+                ))
+            })?;
+        let track = &toc.tracks[idx];
+        let end_lba = toc
+            .tracks
+            .get(idx + 1)
+            .map_or(toc.leadout_lba, |next| next.start_lba);
+
+        Ok(TrackInformation {
+            track_number: track.number.into(),
+            session_number: 1,
+            track_mode: if track.is_audio { 0x00 } else { 0x04 },
+            data_mode: if track.is_audio { 0x0F } else { 0x01 },
+            start_lba: track.start_lba,
+            track_size: end_lba - track.start_lba,
+        })
+    }
+
+    fn read_cd_chunk(
+        &self,
+        lba: u32,
+        sectors: u32,
+        format: SectorReadFormat,
+    ) -> Result<Vec<u8>, CdReaderError> {
+        lock(&self.state.commands).push(MockCommand {
+            lba,
+            sectors,
+            format,
+            issued_at: Instant::now(),
+        });
+
+        if lba.saturating_add(sectors) > self.state.toc.leadout_lba {
+            return Err(CdReaderError::Scsi(ScsiError::check_condition(
+                ScsiOp::ReadCd,
+                Some(lba),
+                Some(sectors),
+                (0x05, 0x21, 0x00),
+            )));
+        }
+
+        if let Some(data) = lock(&self.state.cache).get(lba, sectors, format) {
+            return Ok(data);
+        }
+
+        let mut data = Vec::with_capacity(sectors as usize * format.sector_size());
+        for sector_lba in lba..lba + sectors {
+            data.extend_from_slice(&Self::sector_data(sector_lba, format));
+        }
+        let jitter = match format {
+            SectorReadFormat::Audio => lock(&self.state.jitter).pop_front().unwrap_or(0),
+            _ => 0,
+        };
+        if jitter != 0 {
+            let first = i64::from(lba) * 2352 + i64::from(jitter) * 4;
+            data = (first..first + data.len() as i64)
+                .map(|position| match u32::try_from(position) {
+                    Ok(position) => pattern_byte(position / 2352, (position % 2352) as usize),
+                    Err(_) => 0,
+                })
+                .collect();
+        }
+
+        let result = match self.take_fault(lba, sectors) {
+            None => Ok(data),
+            Some((
+                _,
+                MockFault::Scsi {
+                    sense_key,
+                    asc,
+                    ascq,
+                },
+            )) => Err(CdReaderError::Scsi(ScsiError::check_condition(
+                ScsiOp::ReadCd,
+                Some(lba),
+                Some(sectors),
+                (sense_key, asc, ascq),
+            ))),
+            Some((_, MockFault::Io(kind))) => Err(CdReaderError::Io(std::io::Error::new(
+                kind,
+                "simulated transport failure",
+            ))),
+            Some((_, MockFault::ShortRead(bytes))) => {
+                data.truncate(bytes);
+                Ok(data)
+            }
+            Some((fault_lba, MockFault::Corrupt)) => {
+                let sector_size = format.sector_size();
+                let at = (fault_lba - lba) as usize * sector_size;
+                for byte in &mut data[at..at + sector_size] {
+                    *byte = !*byte;
+                }
+                // inverting the clean error pointers of a C2 read flags
+                // every byte
+                Ok(data)
+            }
+        };
+
+        if let Ok(data) = &result {
+            lock(&self.state.cache).insert(lba, format, data);
+        }
+        result
+    }
+
+    fn inquiry(&self) -> Result<InquiryData, CdReaderError> {
+        lock(&self.state.inquiry)
+            .clone()
+            .ok_or_else(|| unsupported("INQUIRY"))
+    }
+
+    fn capabilities(&self) -> Result<DriveCapabilities, CdReaderError> {
+        lock(&self.state.capabilities)
+            .clone()
+            .ok_or_else(|| unsupported("MODE SENSE"))
+    }
+
+    fn invalidate_cache(&self, _lba: u32) -> Result<(), CdReaderError> {
+        lock(&self.state.cache).sectors.clear();
+        Ok(())
+    }
+
+    fn eject(&self) -> Result<(), CdReaderError> {
+        let mut tray = lock(&self.state.tray);
+        if tray.locked {
+            // MEDIUM REMOVAL PREVENTED
+            return Err(CdReaderError::Scsi(ScsiError::check_condition(
+                ScsiOp::StartStopUnit,
+                None,
+                None,
+                (0x05, 0x53, 0x02),
+            )));
+        }
+        tray.open = true;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<(), CdReaderError> {
+        lock(&self.state.tray).open = false;
+        Ok(())
+    }
+
+    fn set_tray_locked(&self, locked: bool) -> Result<(), CdReaderError> {
+        lock(&self.state.tray).locked = locked;
+        Ok(())
+    }
+
+    fn set_read_speed(&self, speed: ReadSpeed) -> Result<(), CdReaderError> {
+        *lock(&self.state.read_speed) = Some(speed);
+        Ok(())
+    }
+
+    fn media_state(&self) -> Result<MediaState, CdReaderError> {
+        Ok(lock(&self.state.media_states)
+            .pop_front()
+            .unwrap_or(MediaState::Ready))
+    }
+
+    fn poll_media_event(&self) -> Result<MediaEventStatus, CdReaderError> {
+        Ok(lock(&self.state.media_events)
+            .pop_front()
+            .unwrap_or(MediaEventStatus {
+                event: None,
+                tray_open: false,
+                media_present: true,
+            }))
+    }
+}
+
+/// Byte `idx` of sector `lba`, before any format-specific layout.
+fn pattern_byte(lba: u32, idx: usize) -> u8 {
+    (lba as usize).wrapping_mul(31).wrapping_add(idx) as u8
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::test_support::toc;
+    use crate::{CdReader, ReadOptions, RetryConfig, TrackStreamOptions};
+
+    const MEDIUM_ERROR: MockFault = MockFault::Scsi {
+        sense_key: 0x03,
+        asc: 0x11,
+        ascq: 0x05,
+    };
+
+    fn no_backoff(max_attempts: u8) -> RetryConfig {
+        RetryConfig::default()
+            .with_max_attempts(max_attempts)
+            .with_initial_backoff(Duration::ZERO)
+            .with_max_backoff(Duration::ZERO)
+    }
+
+    fn calls(drive: &MockDrive) -> Vec<(u32, u32)> {
+        drive
+            .commands()
+            .iter()
+            .map(|command| (command.lba, command.sectors))
+            .collect()
+    }
+
+    fn expected_audio(lbas: std::ops::Range<u32>) -> Vec<u8> {
+        lbas.flat_map(|lba| MockDrive::sector_data(lba, SectorReadFormat::Audio))
+            .collect()
+    }
+
+    #[test]
+    fn reduces_the_chunk_around_a_failing_lba_and_recovers() {
+        let drive = MockDrive::audio_cd(&[60]);
+        drive.fail_lba(30, [MEDIUM_ERROR]);
+        let reader = CdReader::from_backend(drive.clone());
+        let toc = reader.read_toc().unwrap();
+
+        let data = reader
+            .read_track_with_options(&toc, 1, &ReadOptions::default().with_retry(no_backoff(4)))
+            .unwrap();
+
+        assert_eq!(data, expected_audio(0..60));
+        assert_eq!(calls(&drive), [(0, 27), (27, 27), (27, 8), (35, 25)]);
+    }
+
+    #[test]
+    fn waits_with_capped_exponential_backoff_between_attempts() {
+        let drive = MockDrive::audio_cd(&[10]);
+        drive.fail_lba(
+            0,
+            [
+                MockFault::Io(std::io::ErrorKind::TimedOut),
+                MockFault::Io(std::io::ErrorKind::TimedOut),
+                MockFault::Io(std::io::ErrorKind::TimedOut),
+            ],
+        );
+        let reader = CdReader::from_backend(drive.clone());
+        let retry = RetryConfig::default()
+            .with_initial_backoff(Duration::from_millis(10))
+            .with_max_backoff(Duration::from_millis(15))
+            .with_chunk_reduction(false);
+
+        reader
+            .read_sector_range(0, 10, &ReadOptions::default().with_retry(retry))
+            .unwrap();
+
+        let commands = drive.commands();
+        assert_eq!(commands.len(), 4);
+        let gaps: Vec<Duration> = commands
+            .windows(2)
+            .map(|pair| pair[1].issued_at - pair[0].issued_at)
+            .collect();
+        assert!(gaps[0] >= Duration::from_millis(10));
+        assert!(gaps[1] >= Duration::from_millis(15));
+        assert!(gaps[2] >= Duration::from_millis(15));
+    }
+
+    #[test]
+    fn returns_the_last_error_once_attempts_are_exhausted() {
+        let drive = MockDrive::audio_cd(&[10]);
+        drive.fail_lba(
+            5,
+            [
+                MockFault::Io(std::io::ErrorKind::TimedOut),
+                MEDIUM_ERROR,
+                MEDIUM_ERROR,
+            ],
+        );
+        let reader = CdReader::from_backend(drive.clone());
+        let options = ReadOptions::default().with_retry(no_backoff(3).with_chunk_reduction(false));
+
+        let err = reader.read_sector_range(0, 10, &options).unwrap_err();
+
+        assert!(matches!(
+            err,
+            CdReaderError::Scsi(ScsiError {
+                lba: Some(0),
+                sense_key: Some(0x03),
+                asc: Some(0x11),
+                ascq: Some(0x05),
+                ..
+            })
+        ));
+        assert_eq!(calls(&drive), [(0, 10), (0, 10), (0, 10)]);
+    }
+
+    #[test]
+    fn retries_short_reads() {
+        let drive = MockDrive::audio_cd(&[4]);
+        drive.fail_lba(0, [MockFault::ShortRead(100)]);
+        let reader = CdReader::from_backend(drive.clone());
+
+        let data = reader
+            .read_sector_range(0, 4, &ReadOptions::default().with_retry(no_backoff(2)))
+            .unwrap();
+
+        assert_eq!(data, expected_audio(0..4));
+        assert_eq!(calls(&drive), [(0, 4), (0, 1), (1, 3)]);
+    }
+
+    #[test]
+    fn stream_keeps_its_position_after_a_failed_chunk() {
+        let drive = MockDrive::audio_cd(&[40]);
+        drive.fail_lba(3, [MEDIUM_ERROR, MEDIUM_ERROR]);
+        let reader = CdReader::from_backend(drive.clone());
+        let toc = reader.read_toc().unwrap();
+        let options = TrackStreamOptions::default()
+            .with_sectors_per_chunk(20)
+            .with_retry(no_backoff(2));
+        let mut stream = reader
+            .open_track_stream_with_options(&toc, 1, options)
+            .unwrap();
+
+        assert!(stream.next_chunk().is_err());
+        assert_eq!(stream.current_sector(), 0);
+
+        assert_eq!(stream.next_chunk().unwrap().unwrap(), expected_audio(0..20));
+        assert_eq!(
+            stream.next_chunk().unwrap().unwrap(),
+            expected_audio(20..40)
+        );
+        assert!(stream.next_chunk().unwrap().is_none());
+        assert_eq!(calls(&drive), [(0, 20), (0, 8), (0, 20), (20, 20)]);
+    }
+
+    #[test]
+    fn reads_tracks_of_a_cd_extra_toc_without_room_for_the_session_gap() {
+        let reader = CdReader::from_backend(MockDrive::new(toc(
+            &[(1, 0, true), (2, 5_000, true), (3, 8_000, false)],
+            20_000,
+        )));
+        let toc = reader.read_toc().unwrap();
+
+        assert_eq!(
+            reader.read_track(&toc, 1).unwrap(),
+            expected_audio(0..5_000)
+        );
+        assert!(reader.read_track(&toc, 2).is_err());
+    }
+}
@@ -1,5 +1,20 @@
 use crate::{Toc, Track};
 
+/// Allocation length used for READ TOC responses.
+pub(crate) const TOC_BUFFER_SIZE: usize = 2048;
+
+/// Build a READ TOC/PMA/ATIP (0x43) command descriptor block for the formatted TOC.
+pub(crate) fn build_read_toc_cdb(allocation_len: usize) -> [u8; 10] {
+    let mut cdb = [0u8; 10];
+    cdb[0] = 0x43; // READ TOC/PMA/ATIP
+    cdb[1] = 0x00; // LBA format
+    cdb[2] = 0x00; // TOC format
+    cdb[6] = 0x00; // Start with the first track/session
+    cdb[7] = ((allocation_len >> 8) & 0xFF) as u8;
+    cdb[8] = (allocation_len & 0xFF) as u8;
+    cdb
+}
+
 pub(crate) fn parse_toc(data: Vec<u8>) -> std::io::Result<Toc> {
     // TOC data format:
     // Bytes 0-1: TOC data length
@@ -66,10 +81,23 @@ pub(crate) fn parse_toc(data: Vec<u8>) -> std::io::Result<Toc> {
     }
 }
 
-fn lba_to_msf(lba: u32) -> (u8, u8, u8) {
+pub(crate) fn lba_to_msf(lba: u32) -> (u8, u8, u8) {
     let total_frames = lba + 150; // MSF addresses are offset by 150
     let minutes = (total_frames / 75 / 60) as u8;
     let seconds = ((total_frames / 75) % 60) as u8;
     let frames = (total_frames % 75) as u8;
     (minutes, seconds, frames)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::build_read_toc_cdb;
+
+    #[test]
+    fn builds_read_toc_cdb() {
+        assert_eq!(
+            build_read_toc_cdb(2048),
+            [0x43, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00]
+        );
+    }
+}
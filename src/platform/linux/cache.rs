@@ -0,0 +1,25 @@
+use super::device::Drive;
+use super::sg_io::{CommandContext, execute_read};
+use crate::cache::{FUA_READ_BUFFER_SIZE, build_read10_fua_cdb};
+use crate::{CdReaderError, ScsiOp};
+
+// drives may have to spin up and seek to honor FUA
+const FUA_TIMEOUT_MS: u32 = 30_000;
+
+pub(super) fn invalidate_cache(drive: &Drive, lba: u32) -> Result<(), CdReaderError> {
+    let mut block = [0u8; FUA_READ_BUFFER_SIZE];
+    let mut cdb = build_read10_fua_cdb(lba);
+    execute_read(
+        drive,
+        &mut cdb,
+        &mut block,
+        FUA_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::Read10,
+            lba: Some(lba),
+            sectors: Some(1),
+        },
+    )?;
+
+    Ok(())
+}
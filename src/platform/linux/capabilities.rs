@@ -0,0 +1,47 @@
+use super::device::Drive;
+use super::sg_io::{CommandContext, execute_read};
+use crate::capabilities::{
+    GET_CONFIGURATION_RESPONSE_SIZE, MODE_SENSE_RESPONSE_SIZE, apply_cd_read_feature,
+    build_get_configuration_cdb, build_mode_sense_capabilities_cdb, parse_capabilities_page,
+};
+use crate::{CdReaderError, DriveCapabilities, ScsiOp};
+
+const CAPABILITIES_TIMEOUT_MS: u32 = 10_000;
+
+pub(super) fn read_capabilities(drive: &Drive) -> Result<DriveCapabilities, CdReaderError> {
+    let mut data = vec![0u8; MODE_SENSE_RESPONSE_SIZE];
+    let mut cdb = build_mode_sense_capabilities_cdb(MODE_SENSE_RESPONSE_SIZE as u16);
+    let transferred = execute_read(
+        drive,
+        &mut cdb,
+        &mut data,
+        CAPABILITIES_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::ModeSense,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+    data.truncate(transferred);
+    let mut capabilities =
+        parse_capabilities_page(&data).map_err(|error| CdReaderError::Parse(error.to_string()))?;
+
+    // GET CONFIGURATION only refines the mode page; pre-MMC-2 drives reject it
+    let mut data = vec![0u8; GET_CONFIGURATION_RESPONSE_SIZE];
+    let mut cdb = build_get_configuration_cdb(GET_CONFIGURATION_RESPONSE_SIZE as u16);
+    if let Ok(transferred) = execute_read(
+        drive,
+        &mut cdb,
+        &mut data,
+        CAPABILITIES_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::GetConfiguration,
+            lba: None,
+            sectors: None,
+        },
+    ) {
+        apply_cd_read_feature(&mut capabilities, &data[..transferred]);
+    }
+
+    Ok(capabilities)
+}
@@ -5,9 +5,12 @@ use std::io;
 use std::os::fd::{AsRawFd, FromRawFd, RawFd};
 use std::path::Path;
 
+use crate::capture::CaptureWriter;
+
 pub(crate) struct Drive {
     // file closes the file descriptor on drop automatically
     file: File,
+    capture: Option<CaptureWriter>,
 }
 
 impl Drive {
@@ -25,18 +28,23 @@ impl Drive {
 
         Ok(Self {
             file: unsafe { File::from_raw_fd(fd) },
+            capture: None,
         })
     }
 
+    /// Open a drive and record every command issued to it into `capture_path`.
+    pub(crate) fn open_with_capture(path: &str, capture_path: &Path) -> io::Result<Self> {
+        let mut drive = Self::open(path)?;
+        drive.capture = Some(CaptureWriter::create(capture_path)?);
+        Ok(drive)
+    }
+
     pub(super) fn fd(&self) -> RawFd {
         self.file.as_raw_fd()
     }
 
-    #[cfg(test)]
-    pub(crate) fn test_drive() -> Self {
-        Self {
-            file: File::open("/dev/null").expect("could not open /dev/null for tests"),
-        }
+    pub(super) fn capture(&self) -> Option<&CaptureWriter> {
+        self.capture.as_ref()
     }
 }
 
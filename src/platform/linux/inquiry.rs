@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use super::device::Drive;
+use super::sg_io::{CommandContext, execute_read};
+use crate::inquiry::{INQUIRY_RESPONSE_SIZE, InquiryData, build_inquiry_cdb, parse_inquiry};
+use crate::{CdReaderError, ScsiOp};
+
+const INQUIRY_TIMEOUT_MS: u32 = 5_000;
+
+pub(super) fn inquiry(drive: &Drive) -> Result<InquiryData, CdReaderError> {
+    let mut data = vec![0u8; INQUIRY_RESPONSE_SIZE];
+    let mut cdb = build_inquiry_cdb(INQUIRY_RESPONSE_SIZE as u8);
+    let transferred = execute_read(
+        drive,
+        &mut cdb,
+        &mut data,
+        INQUIRY_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::Inquiry,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+    data.truncate(transferred);
+
+    parse_inquiry(&data).map_err(|error| CdReaderError::Parse(error.to_string()))
+}
+
+/// Identification the kernel cached from the drive's INQUIRY data when it
+/// attached the device; readable without opening it, so it also works for
+/// drives that are busy or that the user has no access to.
+pub(crate) fn sysfs_inquiry(path: &str) -> Option<InquiryData> {
+    let device = std::fs::canonicalize(path).ok()?;
+    let name = device.file_name()?.to_str()?;
+    let sysfs = Path::new("/sys/class/block").join(name).join("device");
+    let read = |attribute: &str| {
+        std::fs::read_to_string(sysfs.join(attribute))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+
+    Some(InquiryData {
+        peripheral_device_type: read("type")?.parse().ok()?,
+        vendor: read("vendor")?,
+        product: read("model")?,
+        revision: read("rev").unwrap_or_default(),
+    })
+}
@@ -0,0 +1,45 @@
+use super::device::Drive;
+use super::sg_io::{CommandContext, execute_read};
+use crate::media::{build_test_unit_ready_cdb, media_state_from_test_unit_ready};
+use crate::media_events::{
+    EVENT_STATUS_RESPONSE_SIZE, build_get_event_status_cdb, parse_media_event_status,
+};
+use crate::{CdReaderError, MediaEventStatus, MediaState, ScsiOp};
+
+const TEST_UNIT_READY_TIMEOUT_MS: u32 = 5_000;
+
+pub(super) fn media_state(drive: &Drive) -> Result<MediaState, CdReaderError> {
+    let mut cdb = build_test_unit_ready_cdb();
+    let result = execute_read(
+        drive,
+        &mut cdb,
+        &mut [],
+        TEST_UNIT_READY_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::TestUnitReady,
+            lba: None,
+            sectors: None,
+        },
+    );
+
+    media_state_from_test_unit_ready(result.map(drop))
+}
+
+pub(super) fn poll_media_event(drive: &Drive) -> Result<MediaEventStatus, CdReaderError> {
+    let mut data = vec![0u8; EVENT_STATUS_RESPONSE_SIZE];
+    let mut cdb = build_get_event_status_cdb(EVENT_STATUS_RESPONSE_SIZE as u16);
+    let transferred = execute_read(
+        drive,
+        &mut cdb,
+        &mut data,
+        TEST_UNIT_READY_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::GetEventStatusNotification,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+    data.truncate(transferred);
+
+    parse_media_event_status(&data).map_err(CdReaderError::Io)
+}
@@ -1,26 +1,33 @@
+mod cache;
+mod capabilities;
 mod device;
+mod inquiry;
+mod media;
 mod read_cd;
 mod sg_io;
+mod speed;
 mod toc;
 mod track_information;
+mod tray;
 
 pub(crate) use device::{Drive, list_drive_paths};
+pub(crate) use inquiry::sysfs_inquiry;
 
-use crate::{CdReaderError, SectorReadFormat, Toc};
+use crate::{
+    CdReaderError, DriveBackend, DriveCapabilities, InquiryData, MediaEventStatus, MediaState,
+    ReadSpeed, SectorReadFormat, Toc, TrackInformation,
+};
 
-impl Drive {
-    pub(crate) fn read_toc(&self) -> Result<Toc, CdReaderError> {
+impl DriveBackend for Drive {
+    fn read_toc(&self) -> Result<Toc, CdReaderError> {
         toc::read_toc(self)
     }
 
-    pub(crate) fn read_track_information(
-        &self,
-        track_number: u8,
-    ) -> Result<crate::data_reader::track_information::TrackInformation, CdReaderError> {
+    fn read_track_information(&self, track_number: u8) -> Result<TrackInformation, CdReaderError> {
         track_information::read_track_information(self, track_number)
     }
 
-    pub(crate) fn read_cd_chunk(
+    fn read_cd_chunk(
         &self,
         lba: u32,
         sectors: u32,
@@ -28,4 +35,40 @@ impl Drive {
     ) -> Result<Vec<u8>, CdReaderError> {
         read_cd::read_cd_chunk(self, lba, sectors, format)
     }
+
+    fn inquiry(&self) -> Result<InquiryData, CdReaderError> {
+        inquiry::inquiry(self)
+    }
+
+    fn capabilities(&self) -> Result<DriveCapabilities, CdReaderError> {
+        capabilities::read_capabilities(self)
+    }
+
+    fn eject(&self) -> Result<(), CdReaderError> {
+        tray::start_stop_unit(self, false)
+    }
+
+    fn load(&self) -> Result<(), CdReaderError> {
+        tray::start_stop_unit(self, true)
+    }
+
+    fn set_tray_locked(&self, locked: bool) -> Result<(), CdReaderError> {
+        tray::prevent_allow_medium_removal(self, locked)
+    }
+
+    fn set_read_speed(&self, speed: ReadSpeed) -> Result<(), CdReaderError> {
+        speed::set_read_speed(self, speed)
+    }
+
+    fn media_state(&self) -> Result<MediaState, CdReaderError> {
+        media::media_state(self)
+    }
+
+    fn poll_media_event(&self) -> Result<MediaEventStatus, CdReaderError> {
+        media::poll_media_event(self)
+    }
+
+    fn invalidate_cache(&self, lba: u32) -> Result<(), CdReaderError> {
+        cache::invalidate_cache(self, lba)
+    }
 }
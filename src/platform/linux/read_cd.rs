@@ -14,7 +14,7 @@ pub(super) fn read_cd_chunk(
     let mut chunk = vec![0u8; sectors as usize * format.sector_size()];
     let mut cdb = build_read_cd_cdb(lba, sectors, format);
     let transferred = execute_read(
-        drive.fd(),
+        drive,
         &mut cdb,
         &mut chunk,
         READ_CD_TIMEOUT_MS,
@@ -1,9 +1,13 @@
 use libc::{c_uchar, c_void};
-use std::os::fd::RawFd;
 
+use super::device::Drive;
+use crate::capture::{CaptureRecord, CommandOutcome};
+use crate::errors::parse_fixed_sense;
 use crate::{CdReaderError, ScsiError, ScsiOp};
 
 const SG_INFO_CHECK: u32 = 0x1;
+const SG_DXFER_NONE: i32 = -1;
+const SG_DXFER_TO_DEV: i32 = -2;
 const SG_DXFER_FROM_DEV: i32 = -3;
 
 // _IOWR('S', 0x85, struct sg_io_hdr). Typed as `c_ulong` to match the
@@ -45,12 +49,54 @@ pub(super) struct CommandContext {
 }
 
 /// Execute a single SCSI read command and return the number of bytes transferred.
+///
+/// An empty `output` issues the command without a data phase.
+///
+/// When the drive was opened with a capture file, the command and its outcome
+/// are recorded before the result is returned.
 pub(super) fn execute_read(
-    fd: RawFd,
+    drive: &Drive,
     cdb: &mut [u8],
     output: &mut [u8],
     timeout_ms: u32,
     context: CommandContext,
+) -> Result<usize, CdReaderError> {
+    let direction = if output.is_empty() {
+        SG_DXFER_NONE
+    } else {
+        SG_DXFER_FROM_DEV
+    };
+    execute(drive, cdb, direction, output, timeout_ms, context)
+}
+
+/// Execute a single SCSI command that sends `input` to the drive.
+pub(super) fn execute_write(
+    drive: &Drive,
+    cdb: &mut [u8],
+    input: &[u8],
+    timeout_ms: u32,
+    context: CommandContext,
+) -> Result<(), CdReaderError> {
+    // SG_IO takes a mutable buffer pointer for both directions
+    let mut buffer = input.to_vec();
+    execute(
+        drive,
+        cdb,
+        SG_DXFER_TO_DEV,
+        &mut buffer,
+        timeout_ms,
+        context,
+    )
+    .map(drop)
+}
+
+fn execute(
+    drive: &Drive,
+    cdb: &mut [u8],
+    direction: i32,
+    output: &mut [u8],
+    timeout_ms: u32,
+    context: CommandContext,
 ) -> Result<usize, CdReaderError> {
     let mut sense = [0u8; 64];
     let transfer_len = u32::try_from(output.len()).map_err(|_| {
@@ -62,7 +108,7 @@ pub(super) fn execute_read(
 
     let mut header = SgIoHeader {
         interface_id: 'S' as i32,
-        dxfer_direction: SG_DXFER_FROM_DEV,
+        dxfer_direction: direction,
         cmd_len: cdb.len() as u8,
         mx_sb_len: sense.len() as u8,
         iovec_count: 0,
@@ -85,13 +131,40 @@ pub(super) fn execute_read(
         info: 0,
     };
 
-    let result = unsafe { libc::ioctl(fd, SG_IO, &mut header as *mut _) };
-    if result < 0 {
-        return Err(CdReaderError::Io(std::io::Error::last_os_error()));
+    let result = unsafe { libc::ioctl(drive.fd(), SG_IO, &mut header as *mut _) };
+    let transport_error = (result < 0).then(std::io::Error::last_os_error);
+    let failed = header.info & SG_INFO_CHECK != 0 || header.status != 0;
+    let transferred = if header.resid <= 0 {
+        output.len()
+    } else {
+        output.len().saturating_sub(header.resid as usize)
+    };
+
+    if let Some(capture) = drive.capture() {
+        let outcome = if let Some(error) = &transport_error {
+            CommandOutcome::TransportError(error.raw_os_error().unwrap_or(-1))
+        } else if failed {
+            CommandOutcome::CheckCondition {
+                status: header.status,
+                sense: sense[..(header.sb_len_wr as usize).min(sense.len())].to_vec(),
+            }
+        } else if direction == SG_DXFER_FROM_DEV {
+            CommandOutcome::Data(output[..transferred].to_vec())
+        } else {
+            CommandOutcome::Data(Vec::new())
+        };
+        capture.record(&CaptureRecord {
+            cdb: cdb.to_vec(),
+            outcome,
+        });
     }
 
-    if header.info & SG_INFO_CHECK != 0 || header.status != 0 {
-        let (sense_key, asc, ascq) = parse_sense(&sense, header.sb_len_wr);
+    if let Some(error) = transport_error {
+        return Err(CdReaderError::Io(error));
+    }
+
+    if failed {
+        let (sense_key, asc, ascq) = parse_fixed_sense(&sense, header.sb_len_wr);
         return Err(CdReaderError::Scsi(ScsiError {
             op: context.op,
             lba: context.lba,
@@ -103,17 +176,5 @@ pub(super) fn execute_read(
         }));
     }
 
-    if header.resid <= 0 {
-        return Ok(output.len());
-    }
-
-    Ok(output.len().saturating_sub(header.resid as usize))
-}
-
-fn parse_sense(sense: &[u8], written: u8) -> (Option<u8>, Option<u8>, Option<u8>) {
-    if written < 14 || sense.len() < 14 {
-        return (None, None, None);
-    }
-
-    (Some(sense[2] & 0x0F), Some(sense[12]), Some(sense[13]))
+    Ok(transferred)
 }
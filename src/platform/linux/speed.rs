@@ -0,0 +1,43 @@
+use super::device::Drive;
+use super::sg_io::{CommandContext, execute_read, execute_write};
+use crate::speed::{
+    ReadSpeed, build_performance_descriptor, build_set_cd_speed_cdb, build_set_streaming_cdb,
+};
+use crate::{CdReaderError, ScsiOp};
+
+const SPEED_TIMEOUT_MS: u32 = 10_000;
+
+/// Issue both SET CD SPEED and SET STREAMING: older drives only know the
+/// former, and some newer ones only honour the latter for audio reads.
+pub(super) fn set_read_speed(drive: &Drive, speed: ReadSpeed) -> Result<(), CdReaderError> {
+    let mut cdb = build_set_cd_speed_cdb(speed);
+    let cd_speed = execute_read(
+        drive,
+        &mut cdb,
+        &mut [],
+        SPEED_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::SetCdSpeed,
+            lba: None,
+            sectors: None,
+        },
+    );
+
+    let mut cdb = build_set_streaming_cdb();
+    let streaming = execute_write(
+        drive,
+        &mut cdb,
+        &build_performance_descriptor(speed),
+        SPEED_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::SetStreaming,
+            lba: None,
+            sectors: None,
+        },
+    );
+
+    match cd_speed {
+        Ok(_) => Ok(()),
+        Err(error) => streaming.map_err(|_| error),
+    }
+}
@@ -16,7 +16,7 @@ pub(super) fn read_track_information(
     let mut cdb =
         build_read_track_information_cdb(track_number, TRACK_INFORMATION_RESPONSE_SIZE as u16);
     let transferred = execute_read(
-        drive.fd(),
+        drive,
         &mut cdb,
         &mut data,
         TRACK_INFORMATION_TIMEOUT_MS,
@@ -0,0 +1,44 @@
+use super::device::Drive;
+use super::sg_io::{CommandContext, execute_read};
+use crate::tray::{build_prevent_allow_medium_removal_cdb, build_start_stop_unit_cdb};
+use crate::{CdReaderError, ScsiOp};
+
+// the tray mechanism can take several seconds, more with a disc spinning down
+const TRAY_TIMEOUT_MS: u32 = 30_000;
+
+pub(super) fn start_stop_unit(drive: &Drive, load: bool) -> Result<(), CdReaderError> {
+    let mut cdb = build_start_stop_unit_cdb(load);
+    execute_read(
+        drive,
+        &mut cdb,
+        &mut [],
+        TRAY_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::StartStopUnit,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+
+    Ok(())
+}
+
+pub(super) fn prevent_allow_medium_removal(
+    drive: &Drive,
+    prevent: bool,
+) -> Result<(), CdReaderError> {
+    let mut cdb = build_prevent_allow_medium_removal_cdb(prevent);
+    execute_read(
+        drive,
+        &mut cdb,
+        &mut [],
+        TRAY_TIMEOUT_MS,
+        CommandContext {
+            op: ScsiOp::PreventAllowMediumRemoval,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+
+    Ok(())
+}
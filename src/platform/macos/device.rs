@@ -9,6 +9,7 @@ use super::ffi::{MacDriveInfo, cd_free, list_cd_drives, open_cd_raw_device};
 pub(crate) struct Drive {
     // file closes the file descriptor on drop automatically
     file: File,
+    bsd_name: CString,
 }
 
 impl Drive {
@@ -26,6 +27,7 @@ impl Drive {
 
         Ok(Self {
             file: unsafe { File::from_raw_fd(fd) },
+            bsd_name,
         })
     }
 
@@ -33,11 +35,8 @@ impl Drive {
         self.file.as_raw_fd()
     }
 
-    #[cfg(test)]
-    pub(crate) fn test_drive() -> Self {
-        Self {
-            file: File::open("/dev/null").expect("could not open /dev/null for tests"),
-        }
+    pub(super) fn bsd_name(&self) -> &CStr {
+        &self.bsd_name
     }
 }
 
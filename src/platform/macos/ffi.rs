@@ -43,9 +43,14 @@ unsafe extern "C" {
         out_len: *mut u32,
         out_err: *mut MacScsiError,
     ) -> bool;
+    pub(super) fn cd_inquiry(bsd_name: *const libc::c_char, out_buf: *mut u8, out_len: u32)
+    -> bool;
     pub(super) fn cd_free(pointer: *mut libc::c_void);
     pub(super) fn list_cd_drives(out_drives: *mut *mut MacDriveInfo, out_count: *mut u32) -> bool;
     pub(super) fn open_cd_raw_device(bsd_name: *const libc::c_char) -> libc::c_int;
+    pub(super) fn cd_eject(fd: libc::c_int) -> bool;
+    pub(super) fn cd_set_speed(fd: libc::c_int, kilobytes_per_second: u16) -> bool;
+    pub(super) fn cd_media_present(fd: libc::c_int) -> bool;
 }
 
 pub(super) fn map_error(
@@ -0,0 +1,25 @@
+use super::device::Drive;
+use super::ffi::cd_inquiry;
+use crate::CdReaderError;
+use crate::inquiry::{InquiryData, parse_inquiry};
+
+/// IOKit does not pass INQUIRY through the BSD client, so the shim rebuilds
+/// standard INQUIRY data from the registry's "Device Characteristics", which
+/// the SCSI layer fills from the drive's own INQUIRY response.
+pub(super) fn inquiry(drive: &Drive) -> Result<InquiryData, CdReaderError> {
+    let mut data = [0u8; 36];
+    let success = unsafe {
+        cd_inquiry(
+            drive.bsd_name().as_ptr(),
+            data.as_mut_ptr(),
+            data.len() as u32,
+        )
+    };
+    if !success {
+        return Err(CdReaderError::Io(std::io::Error::other(
+            "could not read drive characteristics from the IORegistry",
+        )));
+    }
+
+    parse_inquiry(&data).map_err(|error| CdReaderError::Parse(error.to_string()))
+}
@@ -0,0 +1,18 @@
+use super::device::Drive;
+use super::ffi::cd_media_present;
+use crate::{CdReaderError, MediaState};
+
+/// The BSD client does not pass TEST UNIT READY through, but it fails every
+/// request once the disc is gone, so querying the block count tells whether a
+/// disc is present.
+pub(super) fn media_state(drive: &Drive) -> Result<MediaState, CdReaderError> {
+    if unsafe { cd_media_present(drive.fd()) } {
+        return Ok(MediaState::Ready);
+    }
+
+    let error = std::io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ENXIO | libc::ENODEV | libc::ENOENT) => Ok(MediaState::NoMedium),
+        _ => Err(CdReaderError::Io(error)),
+    }
+}
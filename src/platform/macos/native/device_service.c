@@ -1,5 +1,8 @@
 #include "shim_common.h"
 
+#include <sys/disk.h>
+#include <sys/ioctl.h>
+
 int open_cd_raw_device(const char *bsdName) {
     if (!bsdName || bsdName[0] == '\0') {
         errno = ENODEV;
@@ -20,3 +23,19 @@ int open_cd_raw_device(const char *bsdName) {
     // important not to open with a write flag so it does not need exclusivity
     return open(path, O_RDONLY | O_NONBLOCK);
 }
+
+bool cd_eject(int fd) {
+    // fails with EBUSY while a volume of the disc is still mounted
+    return ioctl(fd, DKIOCEJECT) == 0;
+}
+
+bool cd_set_speed(int fd, uint16_t kilobytesPerSecond) {
+    // kCDSpeedMax is 0xFFFF, the same "as fast as possible" value as MMC uses
+    CDSpeed speed = kilobytesPerSecond;
+    return ioctl(fd, DKIOCCDSETSPEED, &speed) == 0;
+}
+
+bool cd_media_present(int fd) {
+    uint64_t blockCount = 0;
+    return ioctl(fd, DKIOCGETBLOCKCOUNT, &blockCount) == 0;
+}
@@ -0,0 +1,77 @@
+#include "shim_common.h"
+
+static void copy_padded_string(CFDictionaryRef dict, CFStringRef key, uint8_t *dst, size_t len) {
+    memset(dst, ' ', len);
+
+    CFTypeRef value = CFDictionaryGetValue(dict, key);
+    if (!value || CFGetTypeID(value) != CFStringGetTypeID()) {
+        return;
+    }
+
+    char buffer[64] = {0};
+    if (!CFStringGetCString((CFStringRef)value, buffer, sizeof(buffer), kCFStringEncodingASCII)) {
+        return;
+    }
+
+    size_t copied = strlen(buffer);
+    if (copied > len) {
+        copied = len;
+    }
+    memcpy(dst, buffer, copied);
+}
+
+bool cd_inquiry(const char *bsdName, uint8_t *outBuf, uint32_t outLen) {
+    if (!bsdName || !outBuf || outLen < 36) {
+        return false;
+    }
+
+    memset(outBuf, 0, outLen);
+
+    const char *name = bsdName;
+    if (strncmp(name, "/dev/", 5) == 0) {
+        name += 5;
+    }
+    if (strncmp(name, "rdisk", 5) == 0) {
+        name += 1;
+    }
+
+    CFMutableDictionaryRef match = IOBSDNameMatching(kIOMainPortDefault, 0, name);
+    if (!match) {
+        return false;
+    }
+
+    // consumes the matching dictionary
+    io_service_t media = IOServiceGetMatchingService(kIOMainPortDefault, match);
+    if (media == IO_OBJECT_NULL) {
+        return false;
+    }
+
+    CFTypeRef characteristics = IORegistryEntrySearchCFProperty(
+        media,
+        kIOServicePlane,
+        CFSTR(kIOPropertyDeviceCharacteristicsKey),
+        kCFAllocatorDefault,
+        kIORegistryIterateRecursively | kIORegistryIterateParents
+    );
+    IOObjectRelease(media);
+    if (!characteristics) {
+        fprintf(stderr, "[INQUIRY] no device characteristics for %s\n", name);
+        return false;
+    }
+
+    if (CFGetTypeID(characteristics) != CFDictionaryGetTypeID()) {
+        CFRelease(characteristics);
+        return false;
+    }
+
+    CFDictionaryRef dict = (CFDictionaryRef)characteristics;
+    outBuf[0] = 0x05; // peripheral device type: CD/DVD
+    outBuf[3] = 0x02; // response data format
+    outBuf[4] = 31;   // additional length
+    copy_padded_string(dict, CFSTR(kIOPropertyVendorNameKey), outBuf + 8, 8);
+    copy_padded_string(dict, CFSTR(kIOPropertyProductNameKey), outBuf + 16, 16);
+    copy_padded_string(dict, CFSTR(kIOPropertyProductRevisionLevelKey), outBuf + 32, 4);
+
+    CFRelease(characteristics);
+    return true;
+}
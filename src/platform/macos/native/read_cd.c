@@ -9,6 +9,7 @@
 //   1 = Mode1Cooked  -> user, Mode 1, 2048 B/sector
 //   2 = Mode1Raw     -> full Mode 1, 2352 B/sector
 //   3 = Mode2Raw     -> full sector, unknown type, 2352 B/sector
+//   4 = AudioWithC2  -> user and C2 error flags, CDDA, 2646 B/sector
 static bool sector_layout_for_format(uint32_t format_id,
                                      CDSectorArea *outArea,
                                      CDSectorType *outType,
@@ -37,6 +38,11 @@ static bool sector_layout_for_format(uint32_t format_id,
             *outType = kCDSectorTypeUnknown;
             *outSectorSize = 2352;
             return true;
+        case 4:
+            *outArea = (CDSectorArea)(kCDSectorAreaUser | kCDSectorAreaErrorFlags);
+            *outType = kCDSectorTypeCDDA;
+            *outSectorSize = 2352 + 294;
+            return true;
         default:
             return false;
     }
@@ -7,6 +7,7 @@
 #import <IOKit/storage/IOCDMediaBSDClient.h>
 #import <IOKit/storage/IOCDMedia.h>
 #import <IOKit/storage/IOCDTypes.h>
+#import <IOKit/storage/IOStorageDeviceCharacteristics.h>
 
 #include <stdbool.h>
 #include <errno.h>
@@ -35,10 +36,14 @@ typedef struct {
 bool cd_read_toc(int fd, uint8_t **outBuf, uint32_t *outLen, CdScsiError *outErr);
 bool cd_read_track_information(int fd, uint8_t trackNumber, uint8_t **outBuf, uint32_t *outLen, CdScsiError *outErr);
 bool read_cd_sectors(int fd, uint32_t lba, uint32_t sectors, uint32_t format_id, uint8_t **outBuf, uint32_t *outLen, CdScsiError *outErr);
+bool cd_inquiry(const char *bsdName, uint8_t *outBuf, uint32_t outLen);
 void cd_free(void *p);
 
 bool list_cd_drives(CdDriveInfo **outDrives, uint32_t *outCount);
 
 int open_cd_raw_device(const char *bsdName);
+bool cd_eject(int fd);
+bool cd_set_speed(int fd, uint16_t kilobytesPerSecond);
+bool cd_media_present(int fd);
 
 #endif
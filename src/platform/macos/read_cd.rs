@@ -42,6 +42,7 @@ fn format_id(format: SectorReadFormat) -> u32 {
         SectorReadFormat::Mode1Cooked => 1,
         SectorReadFormat::Mode1Raw => 2,
         SectorReadFormat::Mode2Raw => 3,
+        SectorReadFormat::AudioWithC2 => 4,
     }
 }
 
@@ -56,5 +57,6 @@ mod tests {
         assert_eq!(format_id(SectorReadFormat::Mode1Cooked), 1);
         assert_eq!(format_id(SectorReadFormat::Mode1Raw), 2);
         assert_eq!(format_id(SectorReadFormat::Mode2Raw), 3);
+        assert_eq!(format_id(SectorReadFormat::AudioWithC2), 4);
     }
 }
@@ -0,0 +1,11 @@
+use super::device::Drive;
+use super::ffi::cd_set_speed;
+use crate::{CdReaderError, ReadSpeed};
+
+pub(super) fn set_read_speed(drive: &Drive, speed: ReadSpeed) -> Result<(), CdReaderError> {
+    if unsafe { cd_set_speed(drive.fd(), speed.kilobytes_per_second()) } {
+        Ok(())
+    } else {
+        Err(CdReaderError::Io(std::io::Error::last_os_error()))
+    }
+}
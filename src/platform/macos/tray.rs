@@ -0,0 +1,13 @@
+use super::device::Drive;
+use super::ffi::cd_eject;
+use crate::CdReaderError;
+
+/// macOS only exposes ejecting through `DKIOCEJECT`; loading and locking the
+/// tray are left to the system.
+pub(super) fn eject(drive: &Drive) -> Result<(), CdReaderError> {
+    if unsafe { cd_eject(drive.fd()) } {
+        Ok(())
+    } else {
+        Err(CdReaderError::Io(std::io::Error::last_os_error()))
+    }
+}
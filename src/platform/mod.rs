@@ -6,7 +6,7 @@ mod macos;
 mod windows;
 
 #[cfg(target_os = "linux")]
-pub(crate) use linux::{Drive, list_drive_paths};
+pub(crate) use linux::{Drive, list_drive_paths, sysfs_inquiry};
 #[cfg(target_os = "macos")]
 pub(crate) use macos::{Drive, list_drive_paths};
 #[cfg(target_os = "windows")]
@@ -0,0 +1,25 @@
+use super::device::Drive;
+use super::spti::{CommandContext, execute_read};
+use crate::cache::{FUA_READ_BUFFER_SIZE, build_read10_fua_cdb};
+use crate::{CdReaderError, ScsiOp};
+
+// drives may have to spin up and seek to honor FUA
+const FUA_TIMEOUT_SECONDS: u32 = 30;
+
+pub(super) fn invalidate_cache(drive: &Drive, lba: u32) -> Result<(), CdReaderError> {
+    let mut block = [0u8; FUA_READ_BUFFER_SIZE];
+    let cdb = build_read10_fua_cdb(lba);
+    execute_read(
+        drive.handle(),
+        &cdb,
+        &mut block,
+        FUA_TIMEOUT_SECONDS,
+        CommandContext {
+            op: ScsiOp::Read10,
+            lba: Some(lba),
+            sectors: Some(1),
+        },
+    )?;
+
+    Ok(())
+}
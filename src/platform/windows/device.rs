@@ -44,11 +44,6 @@ impl Drive {
     pub(super) fn handle(&self) -> HANDLE {
         self.handle.as_raw_handle()
     }
-
-    #[cfg(test)]
-    pub(crate) fn test_drive() -> Self {
-        Self::open("NUL").expect("could not open NUL device for tests")
-    }
 }
 
 pub(crate) fn list_drive_paths() -> io::Result<Vec<String>> {
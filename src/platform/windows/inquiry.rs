@@ -0,0 +1,25 @@
+use super::device::Drive;
+use super::spti::{CommandContext, execute_read};
+use crate::inquiry::{INQUIRY_RESPONSE_SIZE, InquiryData, build_inquiry_cdb, parse_inquiry};
+use crate::{CdReaderError, ScsiOp};
+
+const INQUIRY_TIMEOUT_SECONDS: u32 = 5;
+
+pub(super) fn inquiry(drive: &Drive) -> Result<InquiryData, CdReaderError> {
+    let mut data = vec![0u8; INQUIRY_RESPONSE_SIZE];
+    let cdb = build_inquiry_cdb(INQUIRY_RESPONSE_SIZE as u8);
+    let transferred = execute_read(
+        drive.handle(),
+        &cdb,
+        &mut data,
+        INQUIRY_TIMEOUT_SECONDS,
+        CommandContext {
+            op: ScsiOp::Inquiry,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+    data.truncate(transferred);
+
+    parse_inquiry(&data).map_err(|error| CdReaderError::Parse(error.to_string()))
+}
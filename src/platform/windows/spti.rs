@@ -3,10 +3,12 @@ use std::ptr;
 
 use windows_sys::Win32::Foundation::HANDLE;
 use windows_sys::Win32::Storage::IscsiDisc::{
-    IOCTL_SCSI_PASS_THROUGH_DIRECT, SCSI_IOCTL_DATA_IN, SCSI_PASS_THROUGH_DIRECT,
+    IOCTL_SCSI_PASS_THROUGH_DIRECT, SCSI_IOCTL_DATA_IN, SCSI_IOCTL_DATA_OUT,
+    SCSI_IOCTL_DATA_UNSPECIFIED, SCSI_PASS_THROUGH_DIRECT,
 };
 use windows_sys::Win32::System::IO::DeviceIoControl;
 
+use crate::errors::parse_fixed_sense;
 use crate::{CdReaderError, ScsiError, ScsiOp};
 
 const SENSE_BUFFER_SIZE: usize = 32;
@@ -25,12 +27,51 @@ pub(super) struct CommandContext {
 }
 
 /// Execute one SCSI read through Windows SPTI and return the transferred byte count.
+///
+/// An empty `output` issues the command without a data phase.
 pub(super) fn execute_read(
     handle: HANDLE,
     cdb: &[u8],
     output: &mut [u8],
     timeout_seconds: u32,
     context: CommandContext,
+) -> Result<usize, CdReaderError> {
+    let direction = if output.is_empty() {
+        SCSI_IOCTL_DATA_UNSPECIFIED
+    } else {
+        SCSI_IOCTL_DATA_IN
+    };
+    execute(handle, cdb, direction, output, timeout_seconds, context)
+}
+
+/// Execute one SCSI command through Windows SPTI that sends `input` to the drive.
+pub(super) fn execute_write(
+    handle: HANDLE,
+    cdb: &[u8],
+    input: &[u8],
+    timeout_seconds: u32,
+    context: CommandContext,
+) -> Result<(), CdReaderError> {
+    // SPTI takes a mutable buffer pointer for both directions
+    let mut buffer = input.to_vec();
+    execute(
+        handle,
+        cdb,
+        SCSI_IOCTL_DATA_OUT,
+        &mut buffer,
+        timeout_seconds,
+        context,
+    )
+    .map(drop)
+}
+
+fn execute(
+    handle: HANDLE,
+    cdb: &[u8],
+    direction: u32,
+    output: &mut [u8],
+    timeout_seconds: u32,
+    context: CommandContext,
 ) -> Result<usize, CdReaderError> {
     if cdb.len() > 16 {
         return Err(invalid_input("SCSI CDB exceeds the Windows 16-byte limit"));
@@ -42,10 +83,14 @@ pub(super) fn execute_read(
 
     wrapper.sptd.Length = size_of::<SCSI_PASS_THROUGH_DIRECT>() as u16;
     wrapper.sptd.CdbLength = cdb.len() as u8;
-    wrapper.sptd.DataIn = SCSI_IOCTL_DATA_IN as u8;
+    wrapper.sptd.DataIn = direction as u8;
     wrapper.sptd.TimeOutValue = timeout_seconds;
     wrapper.sptd.DataTransferLength = transfer_len;
-    wrapper.sptd.DataBuffer = output.as_mut_ptr().cast();
+    wrapper.sptd.DataBuffer = if output.is_empty() {
+        ptr::null_mut()
+    } else {
+        output.as_mut_ptr().cast()
+    };
     wrapper.sptd.SenseInfoLength = wrapper.sense.len() as u8;
     wrapper.sptd.SenseInfoOffset = offset_of!(SptdWithSense, sense) as u32;
     wrapper.sptd.Cdb[..cdb.len()].copy_from_slice(cdb);
@@ -69,7 +114,8 @@ pub(super) fn execute_read(
     }
 
     if wrapper.sptd.ScsiStatus != 0 {
-        let (sense_key, asc, ascq) = parse_sense(&wrapper.sense, wrapper.sptd.SenseInfoLength);
+        let (sense_key, asc, ascq) =
+            parse_fixed_sense(&wrapper.sense, wrapper.sptd.SenseInfoLength);
         return Err(CdReaderError::Scsi(ScsiError {
             op: context.op,
             lba: context.lba,
@@ -90,11 +136,3 @@ fn invalid_input(message: &'static str) -> CdReaderError {
         message,
     ))
 }
-
-fn parse_sense(sense: &[u8], written: u8) -> (Option<u8>, Option<u8>, Option<u8>) {
-    if written < 14 || sense.len() < 14 {
-        return (None, None, None);
-    }
-
-    (Some(sense[2] & 0x0F), Some(sense[12]), Some(sense[13]))
-}
@@ -0,0 +1,44 @@
+use super::device::Drive;
+use super::spti::{CommandContext, execute_read};
+use crate::tray::{build_prevent_allow_medium_removal_cdb, build_start_stop_unit_cdb};
+use crate::{CdReaderError, ScsiOp};
+
+// the tray mechanism can take several seconds, more with a disc spinning down
+const TRAY_TIMEOUT_SECONDS: u32 = 30;
+
+pub(super) fn start_stop_unit(drive: &Drive, load: bool) -> Result<(), CdReaderError> {
+    let cdb = build_start_stop_unit_cdb(load);
+    execute_read(
+        drive.handle(),
+        &cdb,
+        &mut [],
+        TRAY_TIMEOUT_SECONDS,
+        CommandContext {
+            op: ScsiOp::StartStopUnit,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+
+    Ok(())
+}
+
+pub(super) fn prevent_allow_medium_removal(
+    drive: &Drive,
+    prevent: bool,
+) -> Result<(), CdReaderError> {
+    let cdb = build_prevent_allow_medium_removal_cdb(prevent);
+    execute_read(
+        drive.handle(),
+        &cdb,
+        &mut [],
+        TRAY_TIMEOUT_SECONDS,
+        CommandContext {
+            op: ScsiOp::PreventAllowMediumRemoval,
+            lba: None,
+            sectors: None,
+        },
+    )?;
+
+    Ok(())
+}
@@ -7,9 +7,14 @@
 //! macOS) is platform-specific, so it is injected as a closure.
 
 use std::thread::sleep;
+use std::time::Duration;
 
 use crate::data_reader::SectorReadFormat;
-use crate::{CdReaderError, RetryConfig};
+use crate::{
+    CdReaderError, ReadReport, RetriedSector, RetryAction, RetryConfig, UnreadableSectors,
+};
+
+type ReadChunk<'a> = dyn FnMut(u32, u32) -> Result<Vec<u8>, CdReaderError> + 'a;
 
 /// Read `sectors` sectors starting at `start_lba` in the given `format`.
 ///
@@ -17,6 +22,10 @@ use crate::{CdReaderError, RetryConfig};
 /// command and returns the raw bytes for that chunk. The loop owns chunk
 /// sizing, retries, and backoff so platform code only implements the
 /// single-command read.
+///
+/// Each failed attempt is classified by `cfg` to decide whether and when to
+/// retry. Sectors that fail every attempt are handled as its
+/// [`UnreadableSectors`] policy says.
 pub(crate) fn read_sectors_chunked<F>(
     start_lba: u32,
     sectors: u32,
@@ -27,6 +36,59 @@ pub(crate) fn read_sectors_chunked<F>(
 where
     F: FnMut(u32, u32) -> Result<Vec<u8>, CdReaderError>,
 {
+    read_chunked(
+        start_lba,
+        sectors,
+        format,
+        cfg,
+        false,
+        &mut read_chunk,
+        None,
+    )
+}
+
+/// Like [`read_sectors_chunked`], but records retries, chunk sizes and
+/// unreadable sectors in `report`. Unreadable sectors are zero-filled even
+/// with [`UnreadableSectors::Fail`].
+///
+/// A chunk that fails is re-read one sector at a time to find the bad
+/// sectors. Errors that do not point at the disc, such as the drive
+/// disappearing, still fail the read.
+pub(crate) fn read_sectors_reported<F>(
+    start_lba: u32,
+    sectors: u32,
+    format: SectorReadFormat,
+    cfg: &RetryConfig,
+    mut read_chunk: F,
+    report: &mut ReadReport,
+) -> Result<Vec<u8>, CdReaderError>
+where
+    F: FnMut(u32, u32) -> Result<Vec<u8>, CdReaderError>,
+{
+    read_chunked(
+        start_lba,
+        sectors,
+        format,
+        cfg,
+        false,
+        &mut read_chunk,
+        Some(report),
+    )
+}
+
+/// Read loop behind [`read_sectors_chunked`] and [`read_sectors_reported`].
+///
+/// `sector_by_sector` re-reads a chunk that failed every attempt one sector
+/// at a time, counting the attempts already spent on it.
+fn read_chunked(
+    start_lba: u32,
+    sectors: u32,
+    format: SectorReadFormat,
+    cfg: &RetryConfig,
+    sector_by_sector: bool,
+    read_chunk: &mut ReadChunk<'_>,
+    mut report: Option<&mut ReadReport>,
+) -> Result<Vec<u8>, CdReaderError> {
     if sectors > 0 && start_lba.checked_add(sectors - 1).is_none() {
         return Err(invalid_input("sector range exceeds the maximum LBA"));
     }
@@ -34,25 +96,38 @@ where
     let total_bytes = (sectors as usize)
         .checked_mul(format.sector_size())
         .ok_or_else(|| invalid_input("requested byte count is too large"))?;
-    let max_sectors_per_xfer = format.max_sectors_per_xfer();
     let mut out = Vec::<u8>::new();
     out.try_reserve_exact(total_bytes)
         .map_err(|_| invalid_input("could not allocate the requested output buffer"))?;
 
+    let unreadable = match (cfg.unreadable_sectors, &report) {
+        (UnreadableSectors::Fail, Some(_)) => UnreadableSectors::FillSilence,
+        (policy, _) => policy,
+    };
     let mut remaining = sectors;
     let mut lba = start_lba;
     let attempts_total = cfg.max_attempts.max(1);
     let min_chunk = cfg.min_sectors_per_read.max(1);
+    let (max_sectors_per_xfer, prior_attempts) = if sector_by_sector {
+        (1, attempts_total)
+    } else {
+        (format.max_sectors_per_xfer(), 0)
+    };
 
     while remaining > 0 {
         let mut chunk_sectors = remaining.min(max_sectors_per_xfer);
         let mut backoff = cfg.initial_backoff.min(cfg.max_backoff);
         let mut last_err: Option<CdReaderError> = None;
+        // longest short read, every attempt starts at `lba`
+        let mut partial = Vec::new();
 
         for attempt in 1..=attempts_total {
             let result = read_chunk(lba, chunk_sectors).and_then(|chunk| {
                 let expected_len = (chunk_sectors as usize) * format.sector_size();
                 if chunk.len() != expected_len {
+                    if chunk.len() > partial.len() {
+                        partial = chunk.clone();
+                    }
                     return Err(CdReaderError::Io(std::io::Error::new(
                         std::io::ErrorKind::UnexpectedEof,
                         format!(
@@ -66,6 +141,16 @@ where
 
             match result {
                 Ok(chunk) => {
+                    if let Some(report) = report.as_deref_mut() {
+                        report.chunk_sizes.push(chunk_sectors);
+                        let attempts = prior_attempts.saturating_add(attempt);
+                        if attempts > 1 {
+                            report.retried_sectors.extend(
+                                (lba..lba + chunk_sectors)
+                                    .map(|lba| RetriedSector { lba, attempts }),
+                            );
+                        }
+                    }
                     out.extend_from_slice(&chunk);
                     remaining -= chunk_sectors;
                     if remaining > 0 {
@@ -75,6 +160,13 @@ where
                     break;
                 }
                 Err(err) => {
+                    let delay = match (cfg.classify)(&err) {
+                        RetryAction::Abort => return Err(err),
+                        RetryAction::Retry => backoff,
+                        RetryAction::RetryAfter(_) if cfg.max_backoff.is_zero() => Duration::ZERO,
+                        RetryAction::RetryAfter(delay) => delay.max(backoff),
+                        RetryAction::RetryImmediately => Duration::ZERO,
+                    };
                     last_err = Some(err);
                     if attempt == attempts_total {
                         break;
@@ -82,8 +174,8 @@ where
                     if cfg.reduce_chunk_on_retry && chunk_sectors > min_chunk {
                         chunk_sectors = next_chunk_size(chunk_sectors, min_chunk);
                     }
-                    if !backoff.is_zero() {
-                        sleep(backoff);
+                    if !delay.is_zero() {
+                        sleep(delay);
                     }
                     backoff = backoff.saturating_mul(2).min(cfg.max_backoff);
                 }
@@ -91,13 +183,58 @@ where
         }
 
         if let Some(err) = last_err {
-            return Err(err);
+            if unreadable == UnreadableSectors::Fail || !is_sector_failure(&err) {
+                return Err(err);
+            }
+
+            if chunk_sectors > 1 {
+                let data = read_chunked(
+                    lba,
+                    chunk_sectors,
+                    format,
+                    cfg,
+                    true,
+                    read_chunk,
+                    report.as_deref_mut(),
+                )?;
+                out.extend_from_slice(&data);
+            } else {
+                let sector_end = out.len() + format.sector_size();
+                if unreadable == UnreadableSectors::FillPartial {
+                    partial.truncate(format.sector_size());
+                    out.extend_from_slice(&partial);
+                }
+                out.resize(sector_end, 0);
+                if let Some(report) = report.as_deref_mut() {
+                    report.failed_sectors.push(lba);
+                }
+            }
+            remaining -= chunk_sectors;
+            if remaining > 0 {
+                lba += chunk_sectors;
+            }
         }
     }
 
     Ok(out)
 }
 
+/// Whether a read that failed all attempts is likely caused by the sectors
+/// themselves, so that skipping them lets the rest of the read succeed.
+fn is_sector_failure(error: &CdReaderError) -> bool {
+    match error {
+        CdReaderError::Scsi(_) => true,
+        // short reads, and macOS, which reports failed reads without sense data
+        CdReaderError::Io(error) => matches!(
+            error.kind(),
+            std::io::ErrorKind::UnexpectedEof
+                | std::io::ErrorKind::Other
+                | std::io::ErrorKind::TimedOut
+        ),
+        _ => false,
+    }
+}
+
 fn invalid_input(message: &'static str) -> CdReaderError {
     CdReaderError::Io(std::io::Error::new(
         std::io::ErrorKind::InvalidInput,
@@ -120,7 +257,10 @@ mod tests {
     use std::time::Duration;
 
     use super::read_sectors_chunked;
-    use crate::{CdReaderError, RetryConfig, SectorReadFormat};
+    use crate::{
+        CdReaderError, RetryAction, RetryConfig, ScsiError, ScsiOp, SectorReadFormat,
+        UnreadableSectors,
+    };
 
     fn retry_config(max_attempts: u8, reduce_chunk_on_retry: bool) -> RetryConfig {
         RetryConfig::default()
@@ -198,6 +338,144 @@ mod tests {
         assert_eq!(data.len(), 2 * 2048);
     }
 
+    /// Reads of 0xA5 sectors where LBA 103 fails, returning `partial` bytes
+    /// when read on its own.
+    fn read_with_bad_sector(
+        policy: UnreadableSectors,
+        partial: usize,
+    ) -> Result<Vec<u8>, CdReaderError> {
+        read_sectors_chunked(
+            100,
+            10,
+            SectorReadFormat::Audio,
+            &retry_config(2, true).with_unreadable_sectors(policy),
+            |lba, sectors| {
+                if !(lba..lba + sectors).contains(&103) {
+                    Ok(vec![0xA5; sectors as usize * 2352])
+                } else if sectors == 1 {
+                    Ok(vec![0xA5; partial])
+                } else {
+                    Err(CdReaderError::Io(std::io::Error::other(
+                        "simulated medium error",
+                    )))
+                }
+            },
+        )
+    }
+
+    #[test]
+    fn fails_on_an_unreadable_sector_by_default() {
+        assert!(read_with_bad_sector(UnreadableSectors::Fail, 0).is_err());
+    }
+
+    #[test]
+    fn fills_unreadable_sectors_with_silence() {
+        let data = read_with_bad_sector(UnreadableSectors::FillSilence, 100).unwrap();
+
+        assert_eq!(data.len(), 10 * 2352);
+        assert!(data[3 * 2352..4 * 2352].iter().all(|byte| *byte == 0));
+        assert!(data[..3 * 2352].iter().all(|byte| *byte == 0xA5));
+        assert!(data[4 * 2352..].iter().all(|byte| *byte == 0xA5));
+    }
+
+    #[test]
+    fn fills_unreadable_sectors_with_partial_reads() {
+        let data = read_with_bad_sector(UnreadableSectors::FillPartial, 100).unwrap();
+
+        let sector = &data[3 * 2352..4 * 2352];
+        assert!(sector[..100].iter().all(|byte| *byte == 0xA5));
+        assert!(sector[100..].iter().all(|byte| *byte == 0));
+        assert!(data[4 * 2352..].iter().all(|byte| *byte == 0xA5));
+    }
+
+    #[test]
+    fn transport_failures_are_not_skipped() {
+        let result = read_sectors_chunked(
+            100,
+            10,
+            SectorReadFormat::Audio,
+            &retry_config(2, true).with_unreadable_sectors(UnreadableSectors::FillSilence),
+            |_, _| {
+                Err(CdReaderError::Io(std::io::Error::from(
+                    std::io::ErrorKind::NotConnected,
+                )))
+            },
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn stops_retrying_when_the_disc_is_gone() {
+        let mut calls = 0;
+        let result = read_sectors_chunked(
+            100,
+            10,
+            SectorReadFormat::Audio,
+            &retry_config(4, true).with_unreadable_sectors(UnreadableSectors::FillSilence),
+            |lba, sectors| {
+                calls += 1;
+                Err(CdReaderError::Scsi(ScsiError::check_condition(
+                    ScsiOp::ReadCd,
+                    Some(lba),
+                    Some(sectors),
+                    (0x02, 0x3A, 0x00),
+                )))
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn skips_the_unit_attention_delay_without_backoff() {
+        let mut calls = 0;
+        let started = std::time::Instant::now();
+        read_sectors_chunked(
+            100,
+            10,
+            SectorReadFormat::Audio,
+            &retry_config(3, false),
+            |lba, sectors| {
+                calls += 1;
+                if calls < 3 {
+                    return Err(CdReaderError::Scsi(ScsiError::check_condition(
+                        ScsiOp::ReadCd,
+                        Some(lba),
+                        Some(sectors),
+                        (0x06, 0x28, 0x00),
+                    )));
+                }
+                Ok(vec![0; sectors as usize * 2352])
+            },
+        )
+        .unwrap();
+
+        assert_eq!(calls, 3);
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn uses_the_configured_classifier() {
+        let mut calls = 0;
+        let result = read_sectors_chunked(
+            100,
+            10,
+            SectorReadFormat::Audio,
+            &retry_config(4, true).with_classifier(|_| RetryAction::Abort),
+            |_, _| {
+                calls += 1;
+                Err(CdReaderError::Io(std::io::Error::other(
+                    "simulated failure",
+                )))
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
     #[test]
     fn rejects_an_overflowing_lba_range_before_reading() {
         let mut called = false;
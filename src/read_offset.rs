@@ -0,0 +1,209 @@
+//! Drive read offset correction for audio reads.
+//!
+//! Most drives deliver audio a fixed number of samples early or late, so two
+//! drives return the same sector range with a small shift. Correcting for that
+//! offset is what makes rips bit-identical across drives, which AccurateRip
+//! and CTDB matching rely on. The corrected range usually straddles sector
+//! boundaries, so one extra sector is read on the side the data is shifted
+//! towards.
+
+use crate::CdReaderError;
+
+pub(crate) const AUDIO_SECTOR_SIZE: i64 = 2352;
+pub(crate) const BYTES_PER_SAMPLE: i64 = 4;
+
+/// Read `sectors` audio sectors starting at `start_lba`, shifted by
+/// `offset_samples`.
+///
+/// `read(lba, sectors, past_end)` reads a sector range; `past_end` is set for
+/// sectors at or after `audio_end_lba`. Those are an overread into the
+/// lead-out (or the CD-Extra session gap): if the drive refuses them, they are
+/// zero-filled instead of failing the whole read. Samples before LBA 0 are
+/// always zero-filled.
+pub(crate) fn read_with_offset<F>(
+    start_lba: u32,
+    sectors: u32,
+    offset_samples: i32,
+    audio_end_lba: Option<u32>,
+    mut read: F,
+) -> Result<Vec<u8>, CdReaderError>
+where
+    F: FnMut(u32, u32, bool) -> Result<Vec<u8>, CdReaderError>,
+{
+    let len = i64::from(sectors) * AUDIO_SECTOR_SIZE;
+    let first_byte =
+        i64::from(start_lba) * AUDIO_SECTOR_SIZE + i64::from(offset_samples) * BYTES_PER_SAMPLE;
+    let first_sector = first_byte.div_euclid(AUDIO_SECTOR_SIZE);
+    let end_sector = (first_byte + len + AUDIO_SECTOR_SIZE - 1).div_euclid(AUDIO_SECTOR_SIZE);
+    let end_sector = end_sector.min(i64::from(u32::MAX));
+    let audio_end = audio_end_lba.map_or(end_sector, i64::from);
+
+    let mut buffer = vec![0u8; ((end_sector - first_sector) * AUDIO_SECTOR_SIZE) as usize];
+    let mut fill = |from: i64, to: i64, past_end: bool| -> Result<(), CdReaderError> {
+        if from >= to {
+            return Ok(());
+        }
+        let expected_len = ((to - from) * AUDIO_SECTOR_SIZE) as usize;
+        let result = read(from as u32, (to - from) as u32, past_end).and_then(|data| {
+            if data.len() != expected_len {
+                return Err(CdReaderError::Io(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "short sector read at LBA {from}: expected {expected_len} bytes, got {}",
+                        data.len()
+                    ),
+                )));
+            }
+            Ok(data)
+        });
+        let data = match result {
+            Ok(data) => data,
+            // the drive cannot overread, keep the silence
+            Err(_) if past_end => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        let at = ((from - first_sector) * AUDIO_SECTOR_SIZE) as usize;
+        buffer[at..at + data.len()].copy_from_slice(&data);
+        Ok(())
+    };
+
+    let readable_from = first_sector.max(0);
+    fill(readable_from, end_sector.min(audio_end), false)?;
+    fill(readable_from.max(audio_end), end_sector, true)?;
+
+    let skip = (first_byte - first_sector * AUDIO_SECTOR_SIZE) as usize;
+    buffer.drain(..skip);
+    buffer.truncate(len as usize);
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_with_offset;
+    use crate::{CdReader, CdReaderError, MockDrive, ReadOptions, SectorReadFormat};
+
+    /// Disc of `total` sectors where byte `i` of the disc holds `i % 251`.
+    fn disc_bytes(from_byte: i64, len: usize, total: u32) -> Vec<u8> {
+        (0..len as i64)
+            .map(|i| {
+                let byte = from_byte + i;
+                if byte < 0 || byte >= i64::from(total) * 2352 {
+                    0
+                } else {
+                    (byte % 251) as u8
+                }
+            })
+            .collect()
+    }
+
+    fn read_disc(
+        total: u32,
+        allow_overread: bool,
+    ) -> impl FnMut(u32, u32, bool) -> Result<Vec<u8>, CdReaderError> {
+        move |lba, sectors, _| {
+            if lba + sectors > total && !allow_overread {
+                return Err(CdReaderError::Io(std::io::Error::other("lead-out")));
+            }
+            Ok((0..sectors as usize * 2352)
+                .map(|i| ((lba as usize * 2352 + i) % 251) as u8)
+                .collect())
+        }
+    }
+
+    #[test]
+    fn shifts_reads_into_the_following_sector() {
+        let data = read_with_offset(10, 3, 6, Some(100), read_disc(100, false)).unwrap();
+
+        assert_eq!(data, disc_bytes(10 * 2352 + 24, 3 * 2352, 100));
+    }
+
+    #[test]
+    fn shifts_reads_into_the_preceding_sector() {
+        let data = read_with_offset(10, 3, -700, Some(100), read_disc(100, false)).unwrap();
+
+        assert_eq!(data, disc_bytes(10 * 2352 - 2800, 3 * 2352, 100));
+    }
+
+    #[test]
+    fn zero_fills_before_lba_zero() {
+        let mut requested = Vec::new();
+        let data = read_with_offset(0, 2, -30, Some(100), |lba, sectors, past_end| {
+            requested.push((lba, sectors, past_end));
+            read_disc(100, false)(lba, sectors, past_end)
+        })
+        .unwrap();
+
+        assert_eq!(requested, vec![(0, 2, false)]);
+        assert!(data[..120].iter().all(|byte| *byte == 0));
+        assert_eq!(data, disc_bytes(-120, 2 * 2352, 100));
+    }
+
+    #[test]
+    fn zero_fills_past_the_end_when_overreading_fails() {
+        let data = read_with_offset(98, 2, 30, Some(100), read_disc(100, false)).unwrap();
+
+        assert_eq!(data, disc_bytes(98 * 2352 + 120, 2 * 2352, 100));
+        assert!(data[2 * 2352 - 120..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn keeps_overread_data_when_the_drive_returns_it() {
+        let data = read_with_offset(98, 2, 30, Some(100), read_disc(100, true)).unwrap();
+
+        assert_eq!(data, disc_bytes(98 * 2352 + 120, 2 * 2352, 101));
+    }
+
+    #[test]
+    fn propagates_failures_inside_the_audio_range() {
+        let result = read_with_offset(10, 2, 6, Some(100), |_, _, _| {
+            Err(CdReaderError::Io(std::io::Error::other("medium error")))
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_short_reads_instead_of_keeping_silence() {
+        let result = read_with_offset(10, 2, 6, Some(100), |_, sectors, _| {
+            Ok(vec![0xA5; sectors as usize * 2352 - 1])
+        });
+
+        match result {
+            Err(CdReaderError::Io(error)) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof)
+            }
+            other => panic!("expected UnexpectedEof, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn track_reads_zero_fill_past_the_leadout_of_a_mock_drive() {
+        let reader = CdReader::from_backend(MockDrive::audio_cd(&[10, 10]));
+        let toc = reader.read_toc().unwrap();
+        let options = ReadOptions::default().with_read_offset(6);
+
+        let data = reader.read_track_with_options(&toc, 2, &options).unwrap();
+
+        let mut expected: Vec<u8> = (10..20)
+            .flat_map(|lba| MockDrive::sector_data(lba, SectorReadFormat::Audio))
+            .collect();
+        expected.drain(..24);
+        expected.extend([0; 24]);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn rejects_offsets_for_data_formats() {
+        let reader = CdReader::from_backend(MockDrive::audio_cd(&[10]));
+        let options = ReadOptions::default()
+            .with_format(SectorReadFormat::Mode1Cooked)
+            .with_read_offset(6);
+
+        match reader.read_sector_range(0, 1, &options) {
+            Err(CdReaderError::Io(error)) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput)
+            }
+            other => panic!("expected InvalidInput, got {other:?}"),
+        }
+    }
+}
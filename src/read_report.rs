@@ -0,0 +1,223 @@
+use crate::jitter::JitterTail;
+use crate::{CdReader, CdReaderError, ReadOptions, Toc, data_reader, utils};
+
+/// Sector that was only read after retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetriedSector {
+    /// Sector address.
+    pub lba: u32,
+    /// Attempts it took to read the sector, including the first one and
+    /// the failed attempts of a larger chunk it was then re-read from.
+    pub attempts: u8,
+}
+
+/// How a read went, returned by [`CdReader::read_sector_range_with_report`]
+/// and [`CdReader::read_track_with_report`].
+///
+/// Addresses are of the sectors read from the disc, so with a read offset
+/// they can differ from the requested range by one sector.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadReport {
+    /// Sectors that needed more than one attempt, in read order.
+    pub retried_sectors: Vec<RetriedSector>,
+    /// Sector count of every successful `READ CD` command, in order. Retries
+    /// shrink the chunks, and failed chunks are re-read one sector at a
+    /// time.
+    pub chunk_sizes: Vec<u32>,
+    /// Sectors that could not be read, in read order. They are zero-filled,
+    /// or hold a partial read with [`UnreadableSectors::FillPartial`](crate::UnreadableSectors::FillPartial).
+    pub failed_sectors: Vec<u32>,
+}
+
+impl ReadReport {
+    /// Whether every sector was read.
+    pub fn is_complete(&self) -> bool {
+        self.failed_sectors.is_empty()
+    }
+}
+
+impl CdReader {
+    /// Read a track, zero-filling the sectors that cannot be read instead of
+    /// failing, and report how the read went.
+    ///
+    /// ```no_run
+    /// use cd_da_reader::{CdReader, ReadOptions};
+    ///
+    /// let reader = CdReader::open_default()?;
+    /// let toc = reader.read_toc()?;
+    /// let (pcm, report) = reader.read_track_with_report(&toc, 1, &ReadOptions::default())?;
+    /// for lba in &report.failed_sectors {
+    ///     eprintln!("LBA {lba} is damaged and was replaced by silence");
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// Errors that are not caused by the disc, such as the drive being
+    /// disconnected, still fail the read.
+    pub fn read_track_with_report(
+        &self,
+        toc: &Toc,
+        track_no: u8,
+        options: &ReadOptions,
+    ) -> Result<(Vec<u8>, ReadReport), CdReaderError> {
+        if let Some(track) = toc.tracks.iter().find(|track| track.number == track_no) {
+            data_reader::validate_track_format(track, options.format())?;
+        }
+
+        let (start_lba, sectors, audio_end_lba) =
+            utils::get_track_read_bounds(toc, track_no).map_err(CdReaderError::Io)?;
+        if let Some(speed) = options.read_speed() {
+            self.set_read_speed(speed)?;
+        }
+        self.read_sectors_with_report(start_lba, sectors, options, Some(audio_end_lba))
+    }
+
+    /// Read an arbitrary range of sectors like
+    /// [`CdReader::read_sector_range`], zero-filling the sectors that cannot
+    /// be read instead of failing, and report how the read went.
+    pub fn read_sector_range_with_report(
+        &self,
+        start_lba: u32,
+        sectors: u32,
+        options: &ReadOptions,
+    ) -> Result<(Vec<u8>, ReadReport), CdReaderError> {
+        if let Some(speed) = options.read_speed() {
+            self.set_read_speed(speed)?;
+        }
+        self.read_sectors_with_report(start_lba, sectors, options, None)
+    }
+
+    fn read_sectors_with_report(
+        &self,
+        start_lba: u32,
+        sectors: u32,
+        options: &ReadOptions,
+        audio_end_lba: Option<u32>,
+    ) -> Result<(Vec<u8>, ReadReport), CdReaderError> {
+        let mut report = ReadReport::default();
+        let data = self.read_sectors_continuing(
+            start_lba,
+            sectors,
+            options,
+            audio_end_lba,
+            &mut JitterTail::default(),
+            Some(&mut report),
+        )?;
+
+        Ok((data, report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        CdReader, MockDrive, MockFault, ReadOptions, RetriedSector, RetryConfig, SectorReadFormat,
+    };
+
+    const MEDIUM_ERROR: MockFault = MockFault::Scsi {
+        sense_key: 0x03,
+        asc: 0x11,
+        ascq: 0x05,
+    };
+
+    fn options() -> ReadOptions {
+        ReadOptions::default().with_retry(
+            RetryConfig::default()
+                .with_max_attempts(2)
+                .with_initial_backoff(Duration::ZERO)
+                .with_max_backoff(Duration::ZERO),
+        )
+    }
+
+    #[test]
+    fn zero_fills_unreadable_sectors_and_continues() {
+        let drive = MockDrive::audio_cd(&[40]);
+        drive.fail_lba(30, [MEDIUM_ERROR; 10]);
+        let reader = CdReader::from_backend(drive);
+
+        let (data, report) = reader
+            .read_sector_range_with_report(0, 40, &options())
+            .unwrap();
+
+        assert_eq!(data.len(), 40 * 2352);
+        assert_eq!(report.failed_sectors, vec![30]);
+        assert!(!report.is_complete());
+        assert!(data[30 * 2352..31 * 2352].iter().all(|&byte| byte == 0));
+        assert_eq!(
+            data[31 * 2352..32 * 2352],
+            MockDrive::sector_data(31, SectorReadFormat::Audio)
+        );
+        // the 13 sectors after the first chunk fail, are reduced to 8 and
+        // fail again, so those 8 are re-read one at a time
+        assert_eq!(report.chunk_sizes, vec![27, 1, 1, 1, 1, 1, 1, 1, 5]);
+    }
+
+    #[test]
+    fn reports_the_neighbours_of_a_bad_sector_as_retried() {
+        let drive = MockDrive::audio_cd(&[27]);
+        drive.fail_lba(13, [MEDIUM_ERROR; 10]);
+        let reader = CdReader::from_backend(drive);
+        let options = options().with_retry(
+            RetryConfig::default()
+                .with_max_attempts(2)
+                .with_initial_backoff(Duration::ZERO)
+                .with_max_backoff(Duration::ZERO)
+                .with_chunk_reduction(false),
+        );
+
+        let (_, report) = reader
+            .read_sector_range_with_report(0, 27, &options)
+            .unwrap();
+
+        assert_eq!(report.failed_sectors, vec![13]);
+        assert_eq!(report.retried_sectors.len(), 26);
+        // two failed reads of the whole chunk, then the sector on its own
+        for lba in [12, 14] {
+            assert!(
+                report
+                    .retried_sectors
+                    .contains(&RetriedSector { lba, attempts: 3 })
+            );
+        }
+    }
+
+    #[test]
+    fn reports_sectors_that_needed_retries() {
+        let drive = MockDrive::audio_cd(&[20]);
+        drive.fail_lba(5, [MEDIUM_ERROR]);
+        let reader = CdReader::from_backend(drive);
+        let toc = reader.read_toc().unwrap();
+
+        let (data, report) = reader.read_track_with_report(&toc, 1, &options()).unwrap();
+
+        assert_eq!(data, reader.read_track(&toc, 1).unwrap());
+        assert!(report.is_complete());
+        assert_eq!(report.chunk_sizes, vec![8, 12]);
+        assert_eq!(report.retried_sectors.len(), 8);
+        assert_eq!(
+            report.retried_sectors[0],
+            RetriedSector {
+                lba: 0,
+                attempts: 2
+            }
+        );
+    }
+
+    #[test]
+    fn transport_failures_still_fail_the_read() {
+        let drive = MockDrive::audio_cd(&[20]);
+        drive.fail_lba(
+            5,
+            std::iter::repeat_n(MockFault::Io(std::io::ErrorKind::NotConnected), 4),
+        );
+        let reader = CdReader::from_backend(drive);
+
+        assert!(
+            reader
+                .read_sector_range_with_report(0, 20, &options())
+                .is_err()
+        );
+    }
+}
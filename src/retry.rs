@@ -1,5 +1,11 @@
 use std::time::Duration;
 
+use crate::CdReaderError;
+
+/// Extra wait before retrying after UNIT ATTENTION, which drives report
+/// while they settle after a disc change or reset.
+const UNIT_ATTENTION_DELAY: Duration = Duration::from_secs(1);
+
 /// Retry policy for read operations.
 ///
 /// The policy is applied when we fail to read a chunk, and it will both
@@ -14,7 +20,10 @@ use std::time::Duration;
 /// - a 20 ms initial backoff;
 /// - a 300 ms maximum backoff;
 /// - adaptive chunk reduction;
-/// - a minimum chunk size of 1 sector.
+/// - a minimum chunk size of 1 sector;
+/// - retries classified by [`RetryAction::for_error`];
+/// - failing the read on sectors that cannot be read
+///   ([`UnreadableSectors::Fail`]).
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
     pub(crate) max_attempts: u8,
@@ -22,6 +31,80 @@ pub struct RetryConfig {
     pub(crate) max_backoff: Duration,
     pub(crate) reduce_chunk_on_retry: bool,
     pub(crate) min_sectors_per_read: u32,
+    pub(crate) unreadable_sectors: UnreadableSectors,
+    pub(crate) classify: fn(&CdReaderError) -> RetryAction,
+}
+
+/// How to follow up on a failed read attempt, decided by the classifier of a
+/// [`RetryConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryAction {
+    /// Give up and return the error, skipping the remaining attempts.
+    Abort,
+    /// Retry after the regular backoff.
+    Retry,
+    /// Retry after the given delay, or the regular backoff if that is longer.
+    /// Like the backoff, the delay is skipped when the maximum backoff is
+    /// zero.
+    RetryAfter(Duration),
+    /// Retry without waiting.
+    RetryImmediately,
+}
+
+impl RetryAction {
+    /// Default classifier, based on the sense data of SCSI errors:
+    ///
+    /// - NOT READY with MEDIUM NOT PRESENT (02/3A) and ILLEGAL REQUEST
+    ///   (05/xx), which includes an out of range LBA (05/21), abort, since
+    ///   retrying cannot help;
+    /// - UNIT ATTENTION (06/xx) waits at least a second, unless retry delays
+    ///   are disabled;
+    /// - unrecovered read errors (03/11) retry without waiting, since a
+    ///   re-read can succeed right away;
+    /// - everything else, including errors without sense data, retries
+    ///   normally.
+    ///
+    /// Custom classifiers can fall back to it:
+    ///
+    /// ```
+    /// use cd_da_reader::{CdReaderError, RetryAction, RetryConfig};
+    ///
+    /// fn classify(error: &CdReaderError) -> RetryAction {
+    ///     match error {
+    ///         CdReaderError::Io(_) => RetryAction::Abort,
+    ///         error => RetryAction::for_error(error),
+    ///     }
+    /// }
+    ///
+    /// let retry = RetryConfig::default().with_classifier(classify);
+    /// ```
+    pub fn for_error(error: &CdReaderError) -> Self {
+        let CdReaderError::Scsi(error) = error else {
+            return Self::Retry;
+        };
+
+        match (error.sense_key, error.asc) {
+            (Some(0x02), Some(0x3A)) | (Some(0x05), _) => Self::Abort,
+            (Some(0x06), _) => Self::RetryAfter(UNIT_ATTENTION_DELAY),
+            (Some(0x03), Some(0x11)) => Self::RetryImmediately,
+            _ => Self::Retry,
+        }
+    }
+}
+
+/// What to do with sectors that still cannot be read when the retries run
+/// out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnreadableSectors {
+    /// Fail the whole read with the last error.
+    #[default]
+    Fail,
+    /// Fill the sectors with zeros, which is silence for audio, and keep
+    /// reading.
+    FillSilence,
+    /// Fill the sectors with the longest partial data any attempt returned,
+    /// padded with zeros, and keep reading.
+    FillPartial,
 }
 
 impl RetryConfig {
@@ -43,7 +126,8 @@ impl RetryConfig {
 
     /// Set the upper bound for exponential backoff delays.
     ///
-    /// A duration of zero disables retry delays.
+    /// A duration of zero disables retry delays, including the ones
+    /// requested with [`RetryAction::RetryAfter`].
     pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
         self.max_backoff = backoff;
         self
@@ -62,6 +146,27 @@ impl RetryConfig {
         self.min_sectors_per_read = sectors.max(1);
         self
     }
+
+    /// Set what happens to sectors that cannot be read.
+    ///
+    /// Only errors that point at the disc, like medium errors and short
+    /// reads, are skipped; transport failures still fail the read. Use
+    /// [`CdReader::read_track_with_report`](crate::CdReader::read_track_with_report)
+    /// to learn which sectors were filled. Secure reads always fail.
+    pub fn with_unreadable_sectors(mut self, policy: UnreadableSectors) -> Self {
+        self.unreadable_sectors = policy;
+        self
+    }
+
+    /// Set the function deciding how to follow up on each failed attempt,
+    /// [`RetryAction::for_error`] by default.
+    ///
+    /// [`RetryAction::Abort`] fails the read even with an
+    /// [`UnreadableSectors`] policy that skips sectors.
+    pub fn with_classifier(mut self, classify: fn(&CdReaderError) -> RetryAction) -> Self {
+        self.classify = classify;
+        self
+    }
 }
 
 impl Default for RetryConfig {
@@ -72,6 +177,8 @@ impl Default for RetryConfig {
             max_backoff: Duration::from_millis(300),
             reduce_chunk_on_retry: true,
             min_sectors_per_read: 1,
+            unreadable_sectors: UnreadableSectors::Fail,
+            classify: RetryAction::for_error,
         }
     }
 }
@@ -79,6 +186,16 @@ impl Default for RetryConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{ScsiError, ScsiOp};
+
+    fn check_condition(sense: (u8, u8, u8)) -> CdReaderError {
+        CdReaderError::Scsi(ScsiError::check_condition(
+            ScsiOp::ReadCd,
+            Some(0),
+            Some(1),
+            sense,
+        ))
+    }
 
     #[test]
     fn default_policy_matches_documented_values() {
@@ -89,6 +206,7 @@ mod tests {
         assert_eq!(config.max_backoff, Duration::from_millis(300));
         assert!(config.reduce_chunk_on_retry);
         assert_eq!(config.min_sectors_per_read, 1);
+        assert_eq!(config.unreadable_sectors, UnreadableSectors::Fail);
     }
 
     #[test]
@@ -98,12 +216,37 @@ mod tests {
             .with_initial_backoff(Duration::from_millis(50))
             .with_max_backoff(Duration::from_secs(1))
             .with_chunk_reduction(false)
-            .with_min_sectors_per_read(0);
+            .with_min_sectors_per_read(0)
+            .with_unreadable_sectors(UnreadableSectors::FillSilence);
 
         assert_eq!(config.max_attempts, 1);
         assert_eq!(config.initial_backoff, Duration::from_millis(50));
         assert_eq!(config.max_backoff, Duration::from_secs(1));
         assert!(!config.reduce_chunk_on_retry);
         assert_eq!(config.min_sectors_per_read, 1);
+        assert_eq!(config.unreadable_sectors, UnreadableSectors::FillSilence);
+    }
+
+    #[test]
+    fn classifies_errors_by_sense_data() {
+        let classify = |sense| RetryAction::for_error(&check_condition(sense));
+
+        assert_eq!(classify((0x02, 0x3A, 0x00)), RetryAction::Abort);
+        assert_eq!(classify((0x05, 0x21, 0x00)), RetryAction::Abort);
+        assert_eq!(classify((0x05, 0x24, 0x00)), RetryAction::Abort);
+        assert_eq!(
+            classify((0x06, 0x28, 0x00)),
+            RetryAction::RetryAfter(UNIT_ATTENTION_DELAY)
+        );
+        assert_eq!(classify((0x03, 0x11, 0x05)), RetryAction::RetryImmediately);
+        assert_eq!(classify((0x03, 0x11, 0x00)), RetryAction::RetryImmediately);
+        // mechanical failures need the backoff
+        assert_eq!(classify((0x03, 0x02, 0x00)), RetryAction::Retry);
+        assert_eq!(classify((0x03, 0x0C, 0x00)), RetryAction::Retry);
+        assert_eq!(classify((0x02, 0x04, 0x01)), RetryAction::Retry);
+        assert_eq!(
+            RetryAction::for_error(&CdReaderError::Io(std::io::Error::other("timeout"))),
+            RetryAction::Retry
+        );
     }
 }
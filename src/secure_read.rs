@@ -0,0 +1,516 @@
+//! Secure (multi-pass) reads.
+//!
+//! A drive reading a scratched disc often returns wrong audio without
+//! reporting any error, because its error correction "conceals" samples it
+//! could not recover. The only way to notice is to read the same sectors
+//! more than once and compare: a sector is trusted once enough reads return
+//! identical data.
+
+use crate::data_reader::{self, SectorReadFormat};
+use crate::read_loop::read_sectors_chunked;
+use crate::read_offset::{self, AUDIO_SECTOR_SIZE, BYTES_PER_SAMPLE};
+use crate::{CdReader, CdReaderError, ReadOptions, RetryConfig, Toc, UnreadableSectors, utils};
+
+/// Options for secure reads, see [`CdReader::read_track_secure`](crate::CdReader::read_track_secure).
+///
+/// The defaults read everything twice, require two identical reads per
+/// sector, and give up on a sector after 16 reads.
+#[derive(Debug, Clone)]
+pub struct SecureReadConfig {
+    pub(crate) passes: u8,
+    pub(crate) required_matches: u8,
+    pub(crate) max_reads: u8,
+}
+
+impl SecureReadConfig {
+    /// Set how many times the whole range is read before sectors are
+    /// compared. A value of zero is normalized to one pass.
+    pub fn with_passes(mut self, passes: u8) -> Self {
+        self.passes = passes.max(1);
+        self
+    }
+
+    /// Set how many identical reads make a sector trusted. A value of zero
+    /// is normalized to one read.
+    pub fn with_required_matches(mut self, matches: u8) -> Self {
+        self.required_matches = matches.max(1);
+        self
+    }
+
+    /// Set the maximum number of reads of one sector, including the full
+    /// passes, before it is reported as unverified.
+    pub fn with_max_reads(mut self, reads: u8) -> Self {
+        self.max_reads = reads.max(1);
+        self
+    }
+}
+
+impl Default for SecureReadConfig {
+    fn default() -> Self {
+        Self {
+            passes: 2,
+            required_matches: 2,
+            max_reads: 16,
+        }
+    }
+}
+
+/// How much a sector of a secure read can be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorConfidence {
+    /// Sector address.
+    pub lba: u32,
+    /// Number of times the sector was read.
+    pub reads: u8,
+    /// Number of reads that returned the data that was kept.
+    pub matching_reads: u8,
+}
+
+/// Data and per-sector confidence returned by a secure read.
+#[derive(Debug, Clone)]
+pub struct SecureRead {
+    /// Sector data, like a regular read returns it.
+    pub data: Vec<u8>,
+    /// Confidence of every sector in `data`, in order.
+    pub sectors: Vec<SectorConfidence>,
+    pub(crate) required_matches: u8,
+}
+
+impl SecureRead {
+    /// Whether every sector reached the required number of identical reads.
+    pub fn is_secure(&self) -> bool {
+        self.unverified_sectors().next().is_none()
+    }
+
+    /// Sectors that did not reach the required number of identical reads
+    /// before the read limit. Their data is the most frequent result, which
+    /// may still be wrong.
+    pub fn unverified_sectors(&self) -> impl Iterator<Item = &SectorConfidence> {
+        self.sectors
+            .iter()
+            .filter(|sector| sector.matching_reads < self.required_matches)
+    }
+}
+
+/// Distinct results read for one sector, with how often each was returned.
+#[derive(Default)]
+struct SectorReads {
+    candidates: Vec<(Vec<u8>, u8)>,
+    reads: u8,
+}
+
+impl SectorReads {
+    fn add(&mut self, data: &[u8]) {
+        self.reads = self.reads.saturating_add(1);
+        match self
+            .candidates
+            .iter_mut()
+            .find(|(candidate, _)| candidate == data)
+        {
+            Some((_, count)) => *count = count.saturating_add(1),
+            None => self.candidates.push((data.to_vec(), 1)),
+        }
+    }
+
+    fn best(&self) -> (&[u8], u8) {
+        self.candidates
+            .iter()
+            .max_by_key(|(_, count)| *count)
+            .map_or((&[][..], 0), |(data, count)| (data.as_slice(), *count))
+    }
+}
+
+/// Read a sector range securely.
+///
+/// The range is read `passes` times with the regular retry loop, then the
+/// sectors without `required_matches` identical results are re-read in
+/// rounds until they have them or were read `max_reads` times.
+/// `defeat_cache(lba, sectors)` runs before every pass after the first and
+/// once per round, covering all sectors re-read in it. Command failures that
+/// survive the retry policy fail the whole read, whatever its
+/// [`UnreadableSectors`] policy, since filled sectors would match each other.
+pub(crate) fn read_secure<F, D>(
+    start_lba: u32,
+    sectors: u32,
+    format: SectorReadFormat,
+    retry: &RetryConfig,
+    config: &SecureReadConfig,
+    mut read_chunk: F,
+    mut defeat_cache: D,
+) -> Result<SecureRead, CdReaderError>
+where
+    F: FnMut(u32, u32) -> Result<Vec<u8>, CdReaderError>,
+    D: FnMut(u32, u32),
+{
+    let retry = &retry
+        .clone()
+        .with_unreadable_sectors(UnreadableSectors::Fail);
+    let sector_size = format.sector_size();
+    let mut reads: Vec<SectorReads> = (0..sectors).map(|_| SectorReads::default()).collect();
+
+    for pass in 0..config.passes {
+        if pass > 0 {
+            defeat_cache(start_lba, sectors);
+        }
+        let data = read_sectors_chunked(start_lba, sectors, format, retry, &mut read_chunk)?;
+        for (sector, chunk) in reads.iter_mut().zip(data.chunks_exact(sector_size)) {
+            sector.add(chunk);
+        }
+    }
+
+    loop {
+        let pending: Vec<u32> = (0..sectors)
+            .filter(|&offset| {
+                let sector = &reads[offset as usize];
+                sector.best().1 < config.required_matches && sector.reads < config.max_reads
+            })
+            .collect();
+        let (Some(&first), Some(&last)) = (pending.first(), pending.last()) else {
+            break;
+        };
+
+        defeat_cache(start_lba + first, last - first + 1);
+        // each sector is read once per round, so consecutive ones can share
+        // a command
+        for run in pending.chunk_by(|a, b| a + 1 == *b) {
+            let data = read_sectors_chunked(
+                start_lba + run[0],
+                run.len() as u32,
+                format,
+                retry,
+                &mut read_chunk,
+            )?;
+            for (offset, chunk) in run.iter().zip(data.chunks_exact(sector_size)) {
+                reads[*offset as usize].add(chunk);
+            }
+        }
+    }
+
+    let mut data = Vec::with_capacity(sectors as usize * sector_size);
+    let mut confidence = Vec::with_capacity(sectors as usize);
+    for (offset, sector) in reads.iter().enumerate() {
+        let (best, matching_reads) = sector.best();
+        data.extend_from_slice(best);
+        confidence.push(SectorConfidence {
+            lba: start_lba + offset as u32,
+            reads: sector.reads,
+            matching_reads,
+        });
+    }
+
+    Ok(SecureRead {
+        data,
+        sectors: confidence,
+        required_matches: config.required_matches,
+    })
+}
+
+impl CdReader {
+    /// Read a track securely: every sector is read until enough reads return
+    /// identical data, see [`SecureReadConfig`].
+    ///
+    /// This takes at least `passes` times as long as
+    /// [`CdReader::read_track_with_options`], and much longer on damaged
+    /// discs. Check [`SecureRead::is_secure`] before trusting the data.
+    ///
+    /// Drives with a read cache may return a cached copy instead of reading
+    /// the disc again, which makes the comparison meaningless; set
+    /// [`ReadOptions::with_cache_defeat`] to prevent that.
+    ///
+    /// ```no_run
+    /// use cd_da_reader::{CdReader, ReadOptions, SecureReadConfig};
+    ///
+    /// let reader = CdReader::open_path("/dev/sr0")?;
+    /// let toc = reader.read_toc()?;
+    /// let read = reader.read_track_secure(
+    ///     &toc,
+    ///     1,
+    ///     &ReadOptions::default(),
+    ///     &SecureReadConfig::default(),
+    /// )?;
+    /// for sector in read.unverified_sectors() {
+    ///     eprintln!("LBA {} may be wrong", sector.lba);
+    /// }
+    /// # Ok::<(), cd_da_reader::CdReaderError>(())
+    /// ```
+    pub fn read_track_secure(
+        &self,
+        toc: &Toc,
+        track_no: u8,
+        options: &ReadOptions,
+        config: &SecureReadConfig,
+    ) -> Result<SecureRead, CdReaderError> {
+        if let Some(track) = toc.tracks.iter().find(|track| track.number == track_no) {
+            data_reader::validate_track_format(track, options.format())?;
+        }
+
+        let (start_lba, sectors, audio_end_lba) =
+            utils::get_track_read_bounds(toc, track_no).map_err(CdReaderError::Io)?;
+        if let Some(speed) = options.read_speed() {
+            self.set_read_speed(speed)?;
+        }
+        self.read_sectors_secure(start_lba, sectors, options, config, Some(audio_end_lba))
+    }
+
+    /// Read an arbitrary range of sectors securely, like
+    /// [`CdReader::read_sector_range`].
+    pub fn read_sector_range_secure(
+        &self,
+        start_lba: u32,
+        sectors: u32,
+        options: &ReadOptions,
+        config: &SecureReadConfig,
+    ) -> Result<SecureRead, CdReaderError> {
+        if let Some(speed) = options.read_speed() {
+            self.set_read_speed(speed)?;
+        }
+        self.read_sectors_secure(start_lba, sectors, options, config, None)
+    }
+
+    /// Secure counterpart of `CdReader::read_sectors`.
+    ///
+    /// With a read offset, each returned sector straddles two sectors read
+    /// from the disc and reports the confidence of the less trusted one.
+    /// Sectors zero-filled because the drive cannot overread are reported
+    /// with no reads.
+    fn read_sectors_secure(
+        &self,
+        start_lba: u32,
+        sectors: u32,
+        options: &ReadOptions,
+        config: &SecureReadConfig,
+        audio_end_lba: Option<u32>,
+    ) -> Result<SecureRead, CdReaderError> {
+        let format = options.format();
+        let mut defeater = options
+            .cache_defeat()
+            .map(|defeat| self.cache_defeater(defeat, format, audio_end_lba))
+            .transpose()?;
+        let mut read = |lba: u32, sectors: u32, retry: &RetryConfig| {
+            read_secure(
+                lba,
+                sectors,
+                format,
+                retry,
+                config,
+                |lba, chunk_sectors| self.drive.read_cd_chunk(lba, chunk_sectors, format),
+                |lba, sectors| {
+                    if let Some(defeater) = defeater.as_mut() {
+                        defeater.defeat(lba, sectors);
+                    }
+                },
+            )
+        };
+
+        if options.read_offset() == 0 {
+            return read(start_lba, sectors, options.retry());
+        }
+        if format != SectorReadFormat::Audio {
+            return Err(CdReaderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "read offset correction is only supported for audio sectors without C2",
+            )));
+        }
+
+        let overread_retry = options.retry().clone().with_max_attempts(1);
+        let mut disc_sectors = Vec::new();
+        let data = read_offset::read_with_offset(
+            start_lba,
+            sectors,
+            options.read_offset(),
+            audio_end_lba,
+            |lba, sectors, past_end| {
+                let retry = if past_end {
+                    &overread_retry
+                } else {
+                    options.retry()
+                };
+                let result = read(lba, sectors, retry)?;
+                disc_sectors.extend(result.sectors);
+                Ok(result.data)
+            },
+        )?;
+
+        let first_disc_lba = disc_sectors
+            .first()
+            .map_or(0, |sector: &SectorConfidence| i64::from(sector.lba));
+        let first_byte = i64::from(start_lba) * AUDIO_SECTOR_SIZE
+            + i64::from(options.read_offset()) * BYTES_PER_SAMPLE;
+        let sectors = (0..i64::from(sectors))
+            .map(|idx| {
+                let from = first_byte + idx * AUDIO_SECTOR_SIZE;
+                let first = from.div_euclid(AUDIO_SECTOR_SIZE);
+                let last = (from + AUDIO_SECTOR_SIZE - 1).div_euclid(AUDIO_SECTOR_SIZE);
+                // disc sectors are contiguous and in order
+                let index = |lba: i64| usize::try_from(lba - first_disc_lba).ok();
+                let worst = (first..=last)
+                    .filter_map(|lba| disc_sectors.get(index(lba)?))
+                    .min_by_key(|sector| sector.matching_reads);
+                SectorConfidence {
+                    lba: start_lba + idx as u32,
+                    reads: worst.map_or(0, |sector| sector.reads),
+                    matching_reads: worst.map_or(0, |sector| sector.matching_reads),
+                }
+            })
+            .collect();
+
+        Ok(SecureRead {
+            data,
+            sectors,
+            required_matches: config.required_matches,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{SecureReadConfig, read_secure};
+    use crate::{
+        CdReader, CdReaderError, MockDrive, MockFault, ReadOptions, RetryConfig, SectorReadFormat,
+    };
+
+    fn no_backoff() -> RetryConfig {
+        RetryConfig::default()
+            .with_initial_backoff(Duration::ZERO)
+            .with_max_backoff(Duration::ZERO)
+    }
+
+    fn sector(lba: u32) -> Vec<u8> {
+        vec![lba as u8; 2352]
+    }
+
+    #[test]
+    fn trusts_sectors_when_both_passes_agree() {
+        let mut calls = Vec::new();
+        let result = read_secure(
+            10,
+            3,
+            SectorReadFormat::Audio,
+            &no_backoff(),
+            &SecureReadConfig::default(),
+            |lba, sectors| {
+                calls.push((lba, sectors));
+                Ok((lba..lba + sectors).flat_map(sector).collect())
+            },
+            |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(calls, [(10, 3), (10, 3)]);
+        assert!(result.is_secure());
+        assert_eq!(result.data, [sector(10), sector(11), sector(12)].concat());
+        assert!(
+            result
+                .sectors
+                .iter()
+                .all(|sector| sector.reads == 2 && sector.matching_reads == 2)
+        );
+    }
+
+    #[test]
+    fn rereads_mismatching_sectors_until_reads_agree() {
+        let mut reads_of_11 = 0;
+        let mut cache_defeats = Vec::new();
+        let result = read_secure(
+            10,
+            3,
+            SectorReadFormat::Audio,
+            &no_backoff(),
+            &SecureReadConfig::default(),
+            |lba, sectors| {
+                Ok((lba..lba + sectors)
+                    .flat_map(|lba| {
+                        if lba != 11 {
+                            return sector(lba);
+                        }
+                        reads_of_11 += 1;
+                        // the first read returns concealed data
+                        if reads_of_11 == 1 {
+                            vec![0xEE; 2352]
+                        } else {
+                            sector(11)
+                        }
+                    })
+                    .collect())
+            },
+            |lba, sectors| cache_defeats.push((lba, sectors)),
+        )
+        .unwrap();
+
+        assert_eq!(cache_defeats, [(10, 3), (11, 1)]);
+        assert!(result.is_secure());
+        assert_eq!(result.data[2352..2 * 2352], sector(11));
+        assert_eq!(result.sectors[1].reads, 3);
+        assert_eq!(result.sectors[1].matching_reads, 2);
+    }
+
+    #[test]
+    fn reports_sectors_that_never_agree() {
+        let mut counter = 0u8;
+        let result = read_secure(
+            0,
+            2,
+            SectorReadFormat::Audio,
+            &no_backoff(),
+            &SecureReadConfig::default().with_max_reads(5),
+            |lba, sectors| {
+                Ok((lba..lba + sectors)
+                    .flat_map(|lba| {
+                        if lba == 1 {
+                            counter += 1;
+                            vec![counter; 2352]
+                        } else {
+                            sector(lba)
+                        }
+                    })
+                    .collect())
+            },
+            |_, _| {},
+        )
+        .unwrap();
+
+        assert!(!result.is_secure());
+        let unverified: Vec<_> = result.unverified_sectors().collect();
+        assert_eq!(unverified.len(), 1);
+        assert_eq!((unverified[0].lba, unverified[0].reads), (1, 5));
+        assert_eq!(unverified[0].matching_reads, 1);
+    }
+
+    #[test]
+    fn propagates_command_failures() {
+        let result = read_secure(
+            0,
+            2,
+            SectorReadFormat::Audio,
+            &no_backoff().with_max_attempts(1),
+            &SecureReadConfig::default(),
+            |_, _| Err(CdReaderError::Io(std::io::Error::other("medium error"))),
+            |_, _| {},
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn secure_track_reads_detect_silent_corruption_on_a_mock_drive() {
+        let drive = MockDrive::audio_cd(&[20]);
+        drive.fail_lba(5, [MockFault::Corrupt]);
+        let reader = CdReader::from_backend(drive);
+        let toc = reader.read_toc().unwrap();
+        let options = ReadOptions::default().with_read_offset(6);
+
+        let result = reader
+            .read_track_secure(&toc, 1, &options, &SecureReadConfig::default())
+            .unwrap();
+
+        assert!(result.is_secure());
+        assert_eq!(result.sectors.len(), 20);
+        assert_eq!(result.sectors[5].reads, 3);
+        assert_eq!(
+            result.data,
+            reader.read_track_with_options(&toc, 1, &options).unwrap()
+        );
+    }
+}
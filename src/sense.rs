@@ -0,0 +1,287 @@
+use std::fmt;
+
+use crate::ScsiError;
+
+/// Sense key of a failed command, the broad class of the failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SenseCategory {
+    /// NO SENSE (0x0): nothing to report.
+    NoSense,
+    /// RECOVERED ERROR (0x1): the command succeeded after the drive
+    /// recovered from an error.
+    RecoveredError,
+    /// NOT READY (0x2): no readable disc, or the drive is still spinning up.
+    NotReady,
+    /// MEDIUM ERROR (0x3): the disc could not be read, usually because it is
+    /// damaged or dirty.
+    MediumError,
+    /// HARDWARE ERROR (0x4): the drive itself failed.
+    HardwareError,
+    /// ILLEGAL REQUEST (0x5): the drive rejected the command or its
+    /// parameters.
+    IllegalRequest,
+    /// UNIT ATTENTION (0x6): the drive was reset or the disc changed since
+    /// the last command.
+    UnitAttention,
+    /// DATA PROTECT (0x7): the data is protected, for example by copy
+    /// protection.
+    DataProtect,
+    /// BLANK CHECK (0x8): the sector is blank.
+    BlankCheck,
+    /// VENDOR SPECIFIC (0x9).
+    VendorSpecific,
+    /// ABORTED COMMAND (0xB): the drive aborted the command, often because of
+    /// a transport problem.
+    AbortedCommand,
+    /// Any other sense key, which drives do not report for reads.
+    Other(u8),
+}
+
+impl SenseCategory {
+    /// Category of a sense key nibble.
+    pub fn from_sense_key(sense_key: u8) -> Self {
+        match sense_key {
+            0x0 => Self::NoSense,
+            0x1 => Self::RecoveredError,
+            0x2 => Self::NotReady,
+            0x3 => Self::MediumError,
+            0x4 => Self::HardwareError,
+            0x5 => Self::IllegalRequest,
+            0x6 => Self::UnitAttention,
+            0x7 => Self::DataProtect,
+            0x8 => Self::BlankCheck,
+            0x9 => Self::VendorSpecific,
+            0xB => Self::AbortedCommand,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for SenseCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::NoSense => "NO SENSE",
+            Self::RecoveredError => "RECOVERED ERROR",
+            Self::NotReady => "NOT READY",
+            Self::MediumError => "MEDIUM ERROR",
+            Self::HardwareError => "HARDWARE ERROR",
+            Self::IllegalRequest => "ILLEGAL REQUEST",
+            Self::UnitAttention => "UNIT ATTENTION",
+            Self::DataProtect => "DATA PROTECT",
+            Self::BlankCheck => "BLANK CHECK",
+            Self::VendorSpecific => "VENDOR SPECIFIC",
+            Self::AbortedCommand => "ABORTED COMMAND",
+            Self::Other(sense_key) => return write!(f, "sense key 0x{sense_key:X}"),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Decoded sense data of a [`ScsiError`], from [`ScsiError::describe`].
+///
+/// Its `Display` reads like "MEDIUM ERROR: L-EC uncorrectable error".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SenseDescription {
+    /// Class of the failure, from the sense key.
+    pub category: SenseCategory,
+    /// Meaning of the ASC and ASCQ as named by SPC and MMC, if known.
+    pub description: Option<&'static str>,
+}
+
+impl fmt::Display for SenseDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.description {
+            Some(description) => write!(f, "{}: {description}", self.category),
+            None => write!(f, "{}", self.category),
+        }
+    }
+}
+
+/// Meaning of an ASC and ASCQ as named by SPC and MMC, for the codes CD
+/// drives report.
+fn additional_sense_description(asc: u8, ascq: u8) -> Option<&'static str> {
+    let description = match (asc, ascq) {
+        (0x00, 0x00) => "no additional sense information",
+        (0x00, 0x11) => "audio play operation in progress",
+        (0x02, 0x00) => "no seek complete",
+        (0x04, 0x00) => "logical unit not ready, cause not reportable",
+        (0x04, 0x01) => "logical unit is in process of becoming ready",
+        (0x04, 0x02) => "logical unit not ready, initializing command required",
+        (0x04, 0x03) => "logical unit not ready, manual intervention required",
+        (0x04, 0x07) => "logical unit not ready, operation in progress",
+        (0x04, 0x08) => "logical unit not ready, long write in progress",
+        (0x04, _) => "logical unit not ready",
+        (0x05, 0x00) => "logical unit does not respond to selection",
+        (0x06, 0x00) => "no reference position found",
+        (0x08, 0x00) => "logical unit communication failure",
+        (0x08, 0x01) => "logical unit communication time-out",
+        (0x08, 0x03) => "logical unit communication CRC error",
+        (0x09, 0x00) => "track following error",
+        (0x09, 0x01) => "tracking servo failure",
+        (0x09, 0x02) => "focus servo failure",
+        (0x09, 0x03) => "spindle servo failure",
+        (0x11, 0x00) => "unrecovered read error",
+        (0x11, 0x01) => "read retries exhausted",
+        (0x11, 0x02) => "error too long to correct",
+        (0x11, 0x05) => "L-EC uncorrectable error",
+        (0x11, 0x06) => "CIRC unrecovered error",
+        (0x11, 0x0F) => "error reading UPC/EAN number",
+        (0x11, 0x10) => "error reading ISRC number",
+        (0x11, _) => "unrecovered read error",
+        (0x15, 0x00) => "random positioning error",
+        (0x15, 0x01) => "mechanical positioning error",
+        (0x15, 0x02) => "positioning error detected by read of medium",
+        (0x17, 0x00) => "recovered data with no error correction applied",
+        (0x17, 0x01) => "recovered data with retries",
+        (0x17, 0x07) => "recovered data without ECC, recommend reassignment",
+        (0x17, _) => "recovered data without error correction",
+        (0x18, 0x00) => "recovered data with error correction applied",
+        (0x18, 0x01) => "recovered data with error correction and retries applied",
+        (0x18, 0x04) => "recovered data with L-EC",
+        (0x18, 0x05) => "recovered data, recommend reassignment",
+        (0x18, _) => "recovered data with error correction",
+        (0x1A, 0x00) => "parameter list length error",
+        (0x20, 0x00) => "invalid command operation code",
+        (0x21, 0x00) => "logical block address out of range",
+        (0x21, 0x01) => "invalid element address",
+        (0x21, 0x02) => "invalid address for write",
+        (0x24, 0x00) => "invalid field in CDB",
+        (0x25, 0x00) => "logical unit not supported",
+        (0x26, 0x00) => "invalid field in parameter list",
+        (0x26, 0x01) => "parameter not supported",
+        (0x26, 0x02) => "parameter value invalid",
+        (0x28, 0x00) => "not ready to ready change, medium may have changed",
+        (0x28, 0x01) => "import or export element accessed",
+        (0x29, 0x00) => "power on, reset, or bus device reset occurred",
+        (0x29, _) => "power on or reset occurred",
+        (0x2A, 0x01) => "mode parameters changed",
+        (0x2A, _) => "parameters changed",
+        (0x2C, 0x00) => "command sequence error",
+        (0x30, 0x00) => "incompatible medium installed",
+        (0x30, 0x01) => "cannot read medium - unknown format",
+        (0x30, 0x02) => "cannot read medium - incompatible format",
+        (0x30, 0x07) => "cleaning failure",
+        (0x30, _) => "incompatible medium installed",
+        (0x31, 0x00) => "medium format corrupted",
+        (0x3A, 0x00) => "medium not present",
+        (0x3A, 0x01) => "medium not present - tray closed",
+        (0x3A, 0x02) => "medium not present - tray open",
+        (0x3A, _) => "medium not present",
+        (0x3B, 0x0D) => "medium destination element full",
+        (0x3B, 0x0E) => "medium source element empty",
+        (0x3B, 0x11) => "medium magazine not accessible",
+        (0x3E, 0x00) => "logical unit has not self-configured yet",
+        (0x3E, 0x01) => "logical unit failure",
+        (0x3E, 0x02) => "timeout on logical unit",
+        (0x3F, 0x01) => "microcode has been changed",
+        (0x3F, 0x03) => "inquiry data has changed",
+        (0x40, _) => "diagnostic failure",
+        (0x44, 0x00) => "internal target failure",
+        (0x47, 0x00) => "SCSI parity error",
+        (0x48, 0x00) => "initiator detected error message received",
+        (0x4E, 0x00) => "overlapped commands attempted",
+        (0x51, 0x00) => "erase failure",
+        (0x53, 0x00) => "media load or eject failed",
+        (0x53, 0x02) => "medium removal prevented",
+        (0x57, 0x00) => "unable to recover table-of-contents",
+        (0x5A, 0x01) => "operator medium removal request",
+        (0x5D, 0x00) => "failure prediction threshold exceeded",
+        (0x5E, 0x00) => "low power condition on",
+        (0x63, 0x00) => "end of user area encountered on this track",
+        (0x63, 0x01) => "packet does not fit in available space",
+        (0x64, 0x00) => "illegal mode for this track",
+        (0x64, 0x01) => "invalid packet size",
+        (0x6F, 0x00) => "copy protection key exchange failure - authentication failure",
+        (0x6F, 0x01) => "copy protection key exchange failure - key not present",
+        (0x6F, 0x02) => "copy protection key exchange failure - key not established",
+        (0x6F, 0x03) => "read of scrambled sector without authentication",
+        (0x6F, 0x04) => "media region code is mismatched to logical unit region",
+        (0x6F, 0x05) => "drive region must be permanent/region reset count error",
+        (0x72, 0x00) => "session fixation error",
+        (0x73, 0x00) => "CD control error",
+        _ => return None,
+    };
+    Some(description)
+}
+
+impl ScsiError {
+    /// Decode the sense data into a category and, for known ASC and ASCQ
+    /// combinations, a description, for example "MEDIUM ERROR: L-EC
+    /// uncorrectable error" for 03/11/05.
+    ///
+    /// Returns `None` when the drive sent no sense data.
+    pub fn describe(&self) -> Option<SenseDescription> {
+        let category = SenseCategory::from_sense_key(self.sense_key?);
+        let description = match (self.asc, self.ascq) {
+            (Some(asc), Some(ascq)) => additional_sense_description(asc, ascq),
+            _ => None,
+        };
+
+        Some(SenseDescription {
+            category,
+            description,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CdReaderError, ScsiOp};
+
+    fn check_condition(sense: (u8, u8, u8)) -> ScsiError {
+        ScsiError::check_condition(ScsiOp::ReadCd, Some(1234), Some(27), sense)
+    }
+
+    #[test]
+    fn describes_known_sense_codes() {
+        let description = check_condition((0x03, 0x11, 0x05)).describe().unwrap();
+        assert_eq!(description.category, SenseCategory::MediumError);
+        assert_eq!(description.description, Some("L-EC uncorrectable error"));
+        assert_eq!(
+            description.to_string(),
+            "MEDIUM ERROR: L-EC uncorrectable error"
+        );
+
+        let description = check_condition((0x02, 0x3A, 0x02)).describe().unwrap();
+        assert_eq!(description.category, SenseCategory::NotReady);
+        assert_eq!(
+            description.description,
+            Some("medium not present - tray open")
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_asc_and_the_sense_key() {
+        let description = check_condition((0x03, 0x11, 0x42)).describe().unwrap();
+        assert_eq!(description.description, Some("unrecovered read error"));
+
+        let description = check_condition((0x05, 0xF0, 0x00)).describe().unwrap();
+        assert_eq!(description.category, SenseCategory::IllegalRequest);
+        assert_eq!(description.description, None);
+        assert_eq!(description.to_string(), "ILLEGAL REQUEST");
+
+        let mut error = check_condition((0x03, 0x11, 0x05));
+        error.sense_key = None;
+        assert_eq!(error.describe(), None);
+    }
+
+    #[test]
+    fn displays_decoded_sense_data() {
+        assert_eq!(
+            CdReaderError::Scsi(check_condition((0x03, 0x11, 0x05))).to_string(),
+            "SCSI ReadCd failed at LBA 1234 (27 sectors): MEDIUM ERROR: L-EC uncorrectable error (sense 03/11/05)"
+        );
+
+        let mut error = check_condition((0, 0, 0));
+        error.lba = None;
+        error.sectors = None;
+        error.sense_key = None;
+        error.asc = None;
+        error.ascq = None;
+        assert_eq!(
+            CdReaderError::Scsi(error).to_string(),
+            "SCSI ReadCd failed with status 0x02 and no sense data"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+/// Size of the SET STREAMING performance descriptor.
+pub(crate) const PERFORMANCE_DESCRIPTOR_SIZE: usize = 28;
+
+/// Read speed requested from the drive.
+///
+/// Drives round the request to a speed they support, usually down, and some
+/// ignore it for audio reads altogether.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadSpeed {
+    /// The fastest speed the drive supports, which is also its default.
+    Max,
+    /// A multiple of the 1x audio data rate of 176 400 bytes per second,
+    /// for example `Multiple(8)` for 8x.
+    Multiple(u16),
+    /// A raw data rate in bytes per second.
+    BytesPerSecond(u32),
+}
+
+impl ReadSpeed {
+    /// Speed in the kilobytes (1000 bytes) per second used by MMC commands,
+    /// saturated to `0xFFFF`, which drives read as their maximum speed.
+    pub fn kilobytes_per_second(self) -> u16 {
+        let bytes_per_second = match self {
+            Self::Max => return u16::MAX,
+            Self::Multiple(multiple) => u64::from(multiple) * 176_400,
+            Self::BytesPerSecond(bytes) => u64::from(bytes),
+        };
+
+        u16::try_from(bytes_per_second / 1000).unwrap_or(u16::MAX)
+    }
+}
+
+/// Build a SET CD SPEED (0xBB) command descriptor block. The write speed is
+/// left at the drive's maximum.
+pub(crate) fn build_set_cd_speed_cdb(speed: ReadSpeed) -> [u8; 12] {
+    let mut cdb = [0u8; 12];
+    cdb[0] = 0xBB;
+    cdb[2..4].copy_from_slice(&speed.kilobytes_per_second().to_be_bytes());
+    cdb[4..6].copy_from_slice(&u16::MAX.to_be_bytes());
+    cdb
+}
+
+/// Build a SET STREAMING (0xB6) command descriptor block for one performance
+/// descriptor.
+pub(crate) fn build_set_streaming_cdb() -> [u8; 12] {
+    let mut cdb = [0u8; 12];
+    cdb[0] = 0xB6;
+    cdb[9..11].copy_from_slice(&(PERFORMANCE_DESCRIPTOR_SIZE as u16).to_be_bytes());
+    cdb
+}
+
+/// Build the SET STREAMING performance descriptor for the whole disc.
+///
+/// [`ReadSpeed::Max`] sets the "restore drive defaults" bit instead of a
+/// speed, which leaves the choice to the drive.
+#[cfg(any(target_os = "linux", target_os = "windows", test))]
+pub(crate) fn build_performance_descriptor(speed: ReadSpeed) -> [u8; PERFORMANCE_DESCRIPTOR_SIZE] {
+    // sizes are in kilobytes per `time` milliseconds
+    const TIME_MS: u32 = 1000;
+
+    let mut descriptor = [0u8; PERFORMANCE_DESCRIPTOR_SIZE];
+    if speed == ReadSpeed::Max {
+        descriptor[0] = 0x04; // RDD
+    }
+    // start LBA 0 up to the end of the disc
+    descriptor[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+    let read_size = u32::from(speed.kilobytes_per_second());
+    descriptor[12..16].copy_from_slice(&read_size.to_be_bytes());
+    descriptor[16..20].copy_from_slice(&TIME_MS.to_be_bytes());
+    descriptor[20..24].copy_from_slice(&u32::from(u16::MAX).to_be_bytes());
+    descriptor[24..28].copy_from_slice(&TIME_MS.to_be_bytes());
+    descriptor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CdReader, MockDrive, ReadOptions, TrackStreamOptions};
+
+    #[test]
+    fn converts_speeds_to_kilobytes_per_second() {
+        assert_eq!(ReadSpeed::Max.kilobytes_per_second(), 0xFFFF);
+        assert_eq!(ReadSpeed::Multiple(1).kilobytes_per_second(), 176);
+        assert_eq!(ReadSpeed::Multiple(8).kilobytes_per_second(), 1411);
+        assert_eq!(
+            ReadSpeed::BytesPerSecond(705_600).kilobytes_per_second(),
+            705
+        );
+        assert_eq!(ReadSpeed::Multiple(1000).kilobytes_per_second(), 0xFFFF);
+    }
+
+    #[test]
+    fn builds_speed_commands() {
+        assert_eq!(
+            build_set_cd_speed_cdb(ReadSpeed::Multiple(4)),
+            [0xBB, 0, 0x02, 0xC1, 0xFF, 0xFF, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            build_set_streaming_cdb(),
+            [0xB6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 28, 0]
+        );
+
+        let descriptor = build_performance_descriptor(ReadSpeed::Multiple(4));
+        assert_eq!(descriptor[0], 0);
+        assert_eq!(descriptor[12..16], 705u32.to_be_bytes());
+        assert_eq!(descriptor[16..20], 1000u32.to_be_bytes());
+        assert_eq!(build_performance_descriptor(ReadSpeed::Max)[0], 0x04);
+    }
+
+    #[test]
+    fn applies_speed_options_before_reading() {
+        let drive = MockDrive::audio_cd(&[10, 10]);
+        let reader = CdReader::from_backend(drive.clone());
+        let toc = reader.read_toc().unwrap();
+
+        reader.read_track(&toc, 1).unwrap();
+        assert_eq!(drive.read_speed(), None);
+
+        let options = ReadOptions::default().with_read_speed(ReadSpeed::Multiple(4));
+        reader.read_track_with_options(&toc, 1, &options).unwrap();
+        assert_eq!(drive.read_speed(), Some(ReadSpeed::Multiple(4)));
+
+        let options = TrackStreamOptions::default().with_read_speed(ReadSpeed::Max);
+        reader
+            .open_track_stream_with_options(&toc, 2, options)
+            .unwrap();
+        assert_eq!(drive.read_speed(), Some(ReadSpeed::Max));
+    }
+}
@@ -1,17 +1,24 @@
 use std::cmp::min;
 
 use crate::data_reader::validate_track_format;
-use crate::{CdReader, CdReaderError, ReadOptions, RetryConfig, SectorReadFormat, Toc, utils};
+use crate::jitter::JitterTail;
+use crate::{
+    CdReader, CdReaderError, ReadOptions, ReadSpeed, RetryConfig, SectorReadFormat, Toc, utils,
+};
 
 /// Options for streamed track reads.
 ///
 /// The defaults read audio sectors in chunks of 27 using the default retry
-/// policy. Use the builder methods to override only the options you need.
+/// policy, without read offset correction, at whatever speed the drive is
+/// set to. Use the builder methods to override only the options you need.
 #[derive(Debug, Clone)]
 pub struct TrackStreamOptions {
     sectors_per_chunk: u32,
     format: SectorReadFormat,
     retry: RetryConfig,
+    read_offset: i32,
+    read_speed: Option<ReadSpeed>,
+    jitter_correction: bool,
 }
 
 impl TrackStreamOptions {
@@ -35,6 +42,35 @@ impl TrackStreamOptions {
         self.sectors_per_chunk = sectors.max(1);
         self
     }
+
+    /// Set the drive's read offset correction, in samples.
+    ///
+    /// Works like [`ReadOptions::with_read_offset`]: every chunk is shifted
+    /// by this many stereo samples, and a non-zero offset is only accepted
+    /// for audio streams.
+    pub fn with_read_offset(mut self, samples: i32) -> Self {
+        self.read_offset = samples;
+        self
+    }
+
+    /// Set the drive's read speed when the stream is opened.
+    ///
+    /// Works like [`ReadOptions::with_read_speed`]; a slow speed also keeps
+    /// the drive quiet during live playback.
+    pub fn with_read_speed(mut self, speed: ReadSpeed) -> Self {
+        self.read_speed = Some(speed);
+        self
+    }
+
+    /// Overlap consecutive chunk reads and stitch them where their audio
+    /// matches, for drives that click at chunk boundaries.
+    ///
+    /// Works like [`ReadOptions::with_jitter_correction`], across chunks
+    /// too: each chunk is aligned to the end of the previous one.
+    pub fn with_jitter_correction(mut self, enabled: bool) -> Self {
+        self.jitter_correction = enabled;
+        self
+    }
 }
 
 impl Default for TrackStreamOptions {
@@ -43,6 +79,9 @@ impl Default for TrackStreamOptions {
             sectors_per_chunk: 27,
             format: SectorReadFormat::Audio,
             retry: RetryConfig::default(),
+            read_offset: 0,
+            read_speed: None,
+            jitter_correction: false,
         }
     }
 }
@@ -57,7 +96,11 @@ pub struct TrackStream<'a> {
     next_lba: u32,
     remaining_sectors: u32,
     total_sectors: u32,
+    /// End of the audio session, where offset-shifted reads stop being required.
+    audio_end_lba: u32,
     options: TrackStreamOptions,
+    /// End of the previous chunk, to align the next one to.
+    jitter_tail: JitterTail,
 }
 
 impl<'a> TrackStream<'a> {
@@ -68,12 +111,28 @@ impl<'a> TrackStream<'a> {
     /// Returns `Ok(None)` when end-of-track is reached. The bytes per sector
     /// depend on the [`SectorReadFormat`] selected in [`TrackStreamOptions`].
     pub fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, CdReaderError> {
-        self.next_chunk_with(|lba, sectors, format, retry| {
+        let reader = self.reader;
+        let read_offset = self.options.read_offset;
+        let jitter_correction = self.options.jitter_correction;
+        let audio_end_lba = self.audio_end_lba;
+        let mut tail = std::mem::take(&mut self.jitter_tail);
+        let chunk = self.next_chunk_with(|lba, sectors, format, retry| {
             let options = ReadOptions::default()
                 .with_format(format)
-                .with_retry(retry.clone());
-            self.reader.read_sector_range(lba, sectors, &options)
-        })
+                .with_retry(retry.clone())
+                .with_read_offset(read_offset)
+                .with_jitter_correction(jitter_correction);
+            reader.read_sectors_continuing(
+                lba,
+                sectors,
+                &options,
+                Some(audio_end_lba),
+                &mut tail,
+                None,
+            )
+        });
+        self.jitter_tail = tail;
+        chunk
     }
 
     fn next_chunk_with<F>(&mut self, mut read_fn: F) -> Result<Option<Vec<u8>>, CdReaderError>
@@ -183,9 +242,24 @@ impl CdReader {
         if let Some(track) = toc.tracks.iter().find(|track| track.number == track_no) {
             validate_track_format(track, options.format)?;
         }
+        if options.read_offset != 0 && options.format != SectorReadFormat::Audio {
+            return Err(CdReaderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "read offset correction is only supported for audio sectors without C2",
+            )));
+        }
+        if options.jitter_correction && options.format != SectorReadFormat::Audio {
+            return Err(CdReaderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "jitter correction is only supported for audio sectors without C2",
+            )));
+        }
 
-        let (start_lba, sectors) =
-            utils::get_track_bounds(toc, track_no).map_err(CdReaderError::Io)?;
+        let (start_lba, sectors, audio_end_lba) =
+            utils::get_track_read_bounds(toc, track_no).map_err(CdReaderError::Io)?;
+        if let Some(speed) = options.read_speed {
+            self.set_read_speed(speed)?;
+        }
 
         Ok(TrackStream {
             reader: self,
@@ -193,7 +267,9 @@ impl CdReader {
             next_lba: start_lba,
             remaining_sectors: sectors,
             total_sectors: sectors,
+            audio_end_lba,
             options,
+            jitter_tail: JitterTail::default(),
         })
     }
 }
@@ -201,7 +277,8 @@ impl CdReader {
 #[cfg(test)]
 mod tests {
     use super::{TrackStream, TrackStreamOptions};
-    use crate::{CdReader, CdReaderError, RetryConfig, SectorReadFormat};
+    use crate::jitter::JitterTail;
+    use crate::{CdReader, CdReaderError, MockDrive, RetryConfig, SectorReadFormat};
 
     fn mk_stream(
         start_lba: u32,
@@ -215,7 +292,9 @@ mod tests {
             next_lba: start_lba,
             remaining_sectors: total_sectors,
             total_sectors,
+            audio_end_lba: start_lba + total_sectors,
             options: TrackStreamOptions::default().with_sectors_per_chunk(sectors_per_chunk),
+            jitter_tail: JitterTail::default(),
         }
     }
 
@@ -225,11 +304,15 @@ mod tests {
         let options = TrackStreamOptions::default()
             .with_format(SectorReadFormat::Mode1Raw)
             .with_retry(retry)
-            .with_sectors_per_chunk(0);
+            .with_sectors_per_chunk(0)
+            .with_read_offset(6)
+            .with_jitter_correction(true);
 
         assert_eq!(options.format, SectorReadFormat::Mode1Raw);
         assert_eq!(options.retry.max_attempts, 9);
         assert_eq!(options.sectors_per_chunk, 1);
+        assert_eq!(options.read_offset, 6);
+        assert!(options.jitter_correction);
     }
 
     #[test]
@@ -324,4 +407,30 @@ mod tests {
         assert_eq!(stream.next_lba, 10_000);
         assert_eq!(stream.remaining_sectors, 100);
     }
+
+    #[test]
+    fn jitter_correction_removes_clicks_between_chunks() {
+        let read_stream = |jitter_correction| {
+            let drive = MockDrive::audio_cd(&[100]);
+            drive.script_jitter([0, 7, -3, 12, 0, -20]);
+            let reader = CdReader::from_backend(drive);
+            let toc = reader.read_toc().unwrap();
+            let options = TrackStreamOptions::default().with_jitter_correction(jitter_correction);
+            let mut stream = reader
+                .open_track_stream_with_options(&toc, 1, options)
+                .unwrap();
+
+            let mut data = Vec::new();
+            while let Some(chunk) = stream.next_chunk().unwrap() {
+                data.extend_from_slice(&chunk);
+            }
+            data
+        };
+        let expected: Vec<u8> = (0..100)
+            .flat_map(|lba| MockDrive::sector_data(lba, SectorReadFormat::Audio))
+            .collect();
+
+        assert_ne!(read_stream(false), expected);
+        assert_eq!(read_stream(true), expected);
+    }
 }
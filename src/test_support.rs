@@ -0,0 +1,44 @@
+//! Fixtures shared by the unit tests.
+
+use crate::parse_toc::lba_to_msf;
+use crate::{Toc, Track};
+
+/// Build a TOC from `(number, start_lba, is_audio)` tuples.
+pub(crate) fn toc(tracks: &[(u8, u32, bool)], leadout_lba: u32) -> Toc {
+    Toc {
+        first_track: tracks[0].0,
+        last_track: tracks[tracks.len() - 1].0,
+        tracks: tracks
+            .iter()
+            .map(|&(number, start_lba, is_audio)| Track {
+                number,
+                start_lba,
+                start_msf: lba_to_msf(start_lba),
+                is_audio,
+            })
+            .collect(),
+        leadout_lba,
+    }
+}
+
+/// The eleven-track audio CD used as a reference for the disc IDs, with
+/// the CDDB ID `990aab0b`.
+pub(crate) fn sample_toc() -> Toc {
+    let starts = [
+        0, 13132, 27967, 47464, 63025, 90420, 104142, 126725, 139887, 164252, 179485,
+    ];
+    let tracks: Vec<_> = starts
+        .iter()
+        .enumerate()
+        .map(|(idx, &start_lba)| (idx as u8 + 1, start_lba, true))
+        .collect();
+    toc(&tracks, 204855)
+}
+
+/// Deterministic PCM data for `sectors` sectors, without short repeating
+/// patterns that could hide checksum bugs.
+pub(crate) fn pcm(sectors: usize) -> Vec<u8> {
+    (0..sectors * 2352)
+        .map(|i| (((i * 7 + 3) * (i >> 11) + i) & 0xFF) as u8)
+        .collect()
+}
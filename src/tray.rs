@@ -0,0 +1,59 @@
+/// Build a START STOP UNIT (0x1B) command descriptor block that ejects
+/// (`load == false`) or loads (`load == true`) the medium.
+pub(crate) fn build_start_stop_unit_cdb(load: bool) -> [u8; 6] {
+    let mut cdb = [0u8; 6];
+    cdb[0] = 0x1B;
+    // LoEj, with Start selecting between loading and ejecting
+    cdb[4] = 0x02 | u8::from(load);
+    cdb
+}
+
+/// Build a PREVENT ALLOW MEDIUM REMOVAL (0x1E) command descriptor block.
+pub(crate) fn build_prevent_allow_medium_removal_cdb(prevent: bool) -> [u8; 6] {
+    let mut cdb = [0u8; 6];
+    cdb[0] = 0x1E;
+    cdb[4] = u8::from(prevent);
+    cdb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_prevent_allow_medium_removal_cdb, build_start_stop_unit_cdb};
+    use crate::{CdReader, CdReaderError, MockDrive};
+
+    #[test]
+    fn builds_tray_command_descriptor_blocks() {
+        assert_eq!(build_start_stop_unit_cdb(false), [0x1B, 0, 0, 0, 0x02, 0]);
+        assert_eq!(build_start_stop_unit_cdb(true), [0x1B, 0, 0, 0, 0x03, 0]);
+        assert_eq!(
+            build_prevent_allow_medium_removal_cdb(true),
+            [0x1E, 0, 0, 0, 0x01, 0]
+        );
+        assert_eq!(
+            build_prevent_allow_medium_removal_cdb(false),
+            [0x1E, 0, 0, 0, 0x00, 0]
+        );
+    }
+
+    #[test]
+    fn locked_tray_refuses_to_eject_until_unlocked() {
+        let drive = MockDrive::audio_cd(&[10]);
+        let reader = CdReader::from_backend(drive.clone());
+
+        reader.lock_tray().unwrap();
+        match reader.eject() {
+            Err(CdReaderError::Scsi(error)) => {
+                assert_eq!((error.sense_key, error.asc), (Some(0x05), Some(0x53)))
+            }
+            other => panic!("expected medium removal prevented, got {other:?}"),
+        }
+        assert!(!drive.is_tray_open());
+
+        reader.unlock_tray().unwrap();
+        reader.eject().unwrap();
+        assert!(drive.is_tray_open());
+
+        reader.load().unwrap();
+        assert!(!drive.is_tray_open());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Toc;
+use crate::{Toc, Track};
 
 const CD_EXTRA_TRAILING_DATA_GAP_SECTORS: u32 = 11_400;
 
@@ -21,6 +21,20 @@ pub(crate) fn get_track_bounds(toc: &Toc, track_no: u8) -> std::io::Result<(u32,
     Ok((start_lba, sectors))
 }
 
+/// Bounds of a track to read, plus the end of the audio session that a read
+/// offset may overread into.
+///
+/// A TOC without room for the CD-Extra session gap only fails reads of the
+/// last audio track; reads of other tracks treat the session as ending with
+/// the track.
+pub(crate) fn get_track_read_bounds(toc: &Toc, track_no: u8) -> std::io::Result<(u32, u32, u32)> {
+    let (start_lba, sectors) = get_track_bounds(toc, track_no)?;
+    let audio_end_lba =
+        first_session_tracks(toc).map_or(start_lba + sectors, |(_, end_lba)| end_lba);
+
+    Ok((start_lba, sectors, audio_end_lba))
+}
+
 fn get_track_end_lba(toc: &Toc, idx: usize) -> std::io::Result<u32> {
     if is_cd_extra_audio_session_boundary(toc, idx) {
         return toc.tracks[idx + 1]
@@ -42,6 +56,25 @@ fn is_cd_extra_audio_session_boundary(toc: &Toc, idx: usize) -> bool {
         && toc.tracks[idx + 1..].iter().all(|track| !track.is_audio)
 }
 
+/// Tracks of the first (audio) session and the lead-out that ends it.
+///
+/// On CD-Extra discs the trailing data tracks are excluded and the lead-out is
+/// placed before the audio/data session gap, which is what disc ID algorithms
+/// expect.
+pub(crate) fn first_session_tracks(toc: &Toc) -> std::io::Result<(&[Track], u32)> {
+    if toc.tracks.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "TOC does not contain any tracks",
+        ));
+    }
+
+    match (0..toc.tracks.len()).find(|&idx| is_cd_extra_audio_session_boundary(toc, idx)) {
+        Some(idx) => Ok((&toc.tracks[..=idx], get_track_end_lba(toc, idx)?)),
+        None => Ok((&toc.tracks, toc.leadout_lba)),
+    }
+}
+
 fn bad_toc_bounds() -> std::io::Error {
     std::io::Error::new(std::io::ErrorKind::InvalidData, "bad TOC bounds")
 }
@@ -74,96 +107,11 @@ pub(crate) fn create_wav_header(pcm_data_size: u32) -> Vec<u8> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::Track;
-
-    fn get_toc() -> Toc {
-        Toc {
-            first_track: 1,
-            last_track: 11,
-            tracks: vec![
-                Track {
-                    number: 1,
-                    start_lba: 0,
-                    start_msf: (0, 2, 0),
-                    is_audio: true,
-                },
-                Track {
-                    number: 2,
-                    start_lba: 13132,
-                    start_msf: (2, 57, 7),
-                    is_audio: true,
-                },
-                Track {
-                    number: 3,
-                    start_lba: 27967,
-                    start_msf: (6, 14, 67),
-                    is_audio: true,
-                },
-                Track {
-                    number: 4,
-                    start_lba: 47464,
-                    start_msf: (10, 34, 64),
-                    is_audio: true,
-                },
-                Track {
-                    number: 5,
-                    start_lba: 63025,
-                    start_msf: (14, 2, 25),
-                    is_audio: true,
-                },
-                Track {
-                    number: 6,
-                    start_lba: 90420,
-                    start_msf: (20, 7, 45),
-                    is_audio: true,
-                },
-                Track {
-                    number: 7,
-                    start_lba: 104142,
-                    start_msf: (23, 10, 42),
-                    is_audio: true,
-                },
-                Track {
-                    number: 8,
-                    start_lba: 126725,
-                    start_msf: (28, 11, 50),
-                    is_audio: true,
-                },
-                Track {
-                    number: 9,
-                    start_lba: 139887,
-                    start_msf: (31, 7, 12),
-                    is_audio: true,
-                },
-                Track {
-                    number: 10,
-                    start_lba: 164252,
-                    start_msf: (36, 32, 2),
-                    is_audio: true,
-                },
-                Track {
-                    number: 11,
-                    start_lba: 179485,
-                    start_msf: (39, 55, 10),
-                    is_audio: true,
-                },
-            ],
-            leadout_lba: 204855,
-        }
-    }
-
-    fn track(number: u8, start_lba: u32, is_audio: bool) -> Track {
-        Track {
-            number,
-            start_lba,
-            start_msf: (0, 0, 0),
-            is_audio,
-        }
-    }
+    use crate::test_support::{sample_toc, toc};
 
     #[test]
     fn finds_non_last_track_bounds_correctly() {
-        let toc = get_toc();
+        let toc = sample_toc();
 
         let result = get_track_bounds(&toc, 5);
         assert!(result.is_ok());
@@ -175,7 +123,7 @@ mod test {
 
     #[test]
     fn finds_last_track_bounds_correctly() {
-        let toc = get_toc();
+        let toc = sample_toc();
 
         let result = get_track_bounds(&toc, 11);
         assert!(result.is_ok());
@@ -186,17 +134,15 @@ mod test {
 
     #[test]
     fn subtracts_cd_extra_gap_for_last_audio_track_before_trailing_data_tracks() {
-        let toc = Toc {
-            first_track: 1,
-            last_track: 4,
-            tracks: vec![
-                track(1, 0, true),
-                track(2, 10_000, true),
-                track(3, 40_000, false),
-                track(4, 80_000, false),
+        let toc = toc(
+            &[
+                (1, 0, true),
+                (2, 10_000, true),
+                (3, 40_000, false),
+                (4, 80_000, false),
             ],
-            leadout_lba: 120_000,
-        };
+            120_000,
+        );
 
         let result = get_track_bounds(&toc, 2);
         assert!(result.is_ok());
@@ -211,17 +157,15 @@ mod test {
 
     #[test]
     fn does_not_subtract_cd_extra_gap_when_audio_track_follows_later() {
-        let toc = Toc {
-            first_track: 1,
-            last_track: 4,
-            tracks: vec![
-                track(1, 0, true),
-                track(2, 10_000, true),
-                track(3, 40_000, false),
-                track(4, 80_000, true),
+        let toc = toc(
+            &[
+                (1, 0, true),
+                (2, 10_000, true),
+                (3, 40_000, false),
+                (4, 80_000, true),
             ],
-            leadout_lba: 120_000,
-        };
+            120_000,
+        );
 
         let result = get_track_bounds(&toc, 2);
         assert!(result.is_ok());
@@ -231,9 +175,17 @@ mod test {
         assert_eq!(sectors, 40_000 - 10_000);
     }
 
+    #[test]
+    fn reads_tracks_before_a_cd_extra_gap_that_does_not_fit() {
+        let toc = toc(&[(1, 0, true), (2, 5_000, true), (3, 8_000, false)], 20_000);
+
+        assert_eq!(get_track_read_bounds(&toc, 1).unwrap(), (0, 5_000, 5_000));
+        assert!(get_track_read_bounds(&toc, 2).is_err());
+    }
+
     #[test]
     fn returns_error_for_invalid_track() {
-        let toc = get_toc();
+        let toc = sample_toc();
 
         let result = get_track_bounds(&toc, 100);
         assert!(result.is_err());